@@ -0,0 +1,60 @@
+//! Compares the per-line tokenizer with the single-pass `DigitMatcher` on a large generated
+//! calibration document.
+//!
+//! Run with `cargo run --release --example calibration_matcher [lines]`.
+
+use std::{env, time::Instant};
+
+use advent_of_code_2023::{
+    puzzle1::{calibration_value, DigitMatcher, Vocabulary},
+    rng::Rng,
+};
+
+const FRAGMENTS: [&str; 16] = [
+    "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "tw", "eigh", "x",
+    "qz", "oneight", "twone", "sevenine",
+];
+
+fn generate(lines: usize) -> String {
+    let mut rng = Rng::new(0x2023_1201);
+    let mut input = String::new();
+    for _ in 0..lines {
+        for _ in 0..8 + rng.below(24) {
+            match rng.below(5) {
+                0 => input.push(char::from(b'0' + rng.below(10) as u8)),
+                1 | 2 => input.push_str(FRAGMENTS[rng.below(FRAGMENTS.len())]),
+                _ => input.push(char::from(b'a' + rng.below(26) as u8)),
+            }
+        }
+        input.push(char::from(b'0' + rng.below(10) as u8));
+        input.push('\n');
+    }
+    input
+}
+
+fn main() {
+    let lines = env::args()
+        .nth(1)
+        .and_then(|lines| lines.parse().ok())
+        .unwrap_or(1_000_000);
+    let input = generate(lines);
+    println!("{lines} lines, {} bytes", input.len());
+    let vocabulary = Vocabulary::english();
+
+    let start = Instant::now();
+    let tokenized = input
+        .lines()
+        .map(|line| calibration_value(line, &vocabulary).unwrap() as u64)
+        .sum::<u64>();
+    println!("tokenizer: {tokenized} in {:.2?}", start.elapsed());
+
+    let start = Instant::now();
+    let matcher = DigitMatcher::new(&vocabulary);
+    let matched = input
+        .lines()
+        .map(|line| matcher.calibration_value(line).unwrap() as u64)
+        .sum::<u64>();
+    println!("matcher:   {matched} in {:.2?}", start.elapsed());
+
+    assert_eq!(tokenized, matched, "Both approaches must agree");
+}
@@ -0,0 +1,78 @@
+//! Compares finding gears by checking every number against every symbol with the lookups through
+//! the `NumberIndex` on a large generated engine schematic.
+//!
+//! Run with `cargo run --release --example schematic_index [size]`.
+
+use std::{env, time::Instant};
+
+use advent_of_code_2023::{
+    puzzle3::{find_adjacents, find_gears, parse_input, GearRule},
+    rng::Rng,
+};
+
+const SYMBOLS: [char; 6] = ['*', '*', '#', '+', '$', '/'];
+
+fn generate(size: usize) -> String {
+    let mut rng = Rng::new(0x2023_1203);
+    let mut input = String::new();
+    for _ in 0..size {
+        let mut row = String::new();
+        while row.len() < size {
+            match rng.below(10) {
+                0 | 1 => {
+                    for _ in 0..1 + rng.below(3) {
+                        row.push(char::from(b'0' + rng.below(10) as u8));
+                    }
+                    row.push('.');
+                }
+                2 => row.push(SYMBOLS[rng.below(SYMBOLS.len())]),
+                _ => row.push('.'),
+            }
+        }
+        row.truncate(size);
+        input.push_str(&row);
+        input.push('\n');
+    }
+    input
+}
+
+fn main() {
+    let size = env::args()
+        .nth(1)
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(400);
+    let schematic = parse_input(&generate(size)).expect("Generated schematic parses");
+    println!(
+        "{size}x{size} schematic, {} numbers, {} symbols",
+        schematic.numbers().len(),
+        schematic.symbols().len()
+    );
+
+    let start = Instant::now();
+    let rule = GearRule::default();
+    let scanned = find_gears(schematic.numbers(), schematic.symbols(), &rule)
+        .iter()
+        .map(|gear| {
+            find_adjacents(gear, schematic.numbers())
+                .iter()
+                .map(|number| u64::from(number.value))
+                .product::<u64>()
+        })
+        .sum::<u64>();
+    println!("every number: {scanned} in {:.2?}", start.elapsed());
+
+    let start = Instant::now();
+    let indexed = schematic
+        .gears(&rule)
+        .iter()
+        .map(|(_, numbers)| {
+            numbers
+                .iter()
+                .map(|number| u64::from(number.value))
+                .product::<u64>()
+        })
+        .sum::<u64>();
+    println!("index:        {indexed} in {:.2?}", start.elapsed());
+
+    assert_eq!(scanned, indexed, "Both approaches must agree");
+}
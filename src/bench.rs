@@ -0,0 +1,248 @@
+use std::{
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::Result,
+    json,
+    solution::{Day, Part},
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Option<Stats> {
+        let mut samples = samples.to_vec();
+        samples.sort();
+        let middle = samples.len() / 2;
+        let median = if samples.len() % 2 == 0 {
+            (*samples.get(middle.checked_sub(1)?)? + samples[middle]) / 2
+        } else {
+            samples[middle]
+        };
+        Some(Stats {
+            min: *samples.first()?,
+            median,
+            max: *samples.last()?,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PartReport {
+    pub part: Part,
+    /// `None` when the part has not been solved yet.
+    pub answer: Option<String>,
+    pub time: Stats,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DayReport {
+    pub day: u8,
+    pub runs: usize,
+    pub parse: Stats,
+    pub parts: Vec<PartReport>,
+}
+
+fn timed<T>(f: impl FnOnce() -> Result<T>) -> Result<(T, Duration)> {
+    let start = Instant::now();
+    let value = f()?;
+    Ok((value, start.elapsed()))
+}
+
+/// Parses and solves the given parts `runs` times, measuring parsing and every part separately.
+pub fn measure(day: &Day, input: &str, parts: &[Part], runs: usize) -> Result<DayReport> {
+    let runs = runs.max(1);
+    let mut parse_samples = vec![];
+    let mut part_samples = vec![vec![]; parts.len()];
+    let mut answers = vec![None; parts.len()];
+
+    for _ in 0..runs {
+        let (parsed_input, parse_time) = timed(|| day.parse(input))?;
+        parse_samples.push(parse_time);
+
+        for (idx, &part) in parts.iter().enumerate() {
+            let (answer, solve_time) = timed(|| parsed_input.solve(part))?;
+            part_samples[idx].push(solve_time);
+            answers[idx] = answer;
+        }
+    }
+
+    Ok(DayReport {
+        day: day.number,
+        runs,
+        parse: Stats::from_samples(&parse_samples).expect("At least one run"),
+        parts: parts
+            .iter()
+            .zip(answers)
+            .zip(part_samples)
+            .map(|((&part, answer), samples)| PartReport {
+                part,
+                answer,
+                time: Stats::from_samples(&samples).expect("At least one run"),
+            })
+            .collect(),
+    })
+}
+
+fn table_row(table: &mut String, day: u8, step: &str, answer: &str, time: &Stats) {
+    writeln!(
+        table,
+        "{day:>3}  {step:<8}  {answer:>16}  {:>12.2?}  {:>12.2?}  {:>12.2?}",
+        time.min, time.median, time.max
+    )
+    .unwrap();
+}
+
+pub fn render_table(reports: &[DayReport]) -> String {
+    let mut table = format!(
+        "{:>3}  {:<8}  {:>16}  {:>12}  {:>12}  {:>12}\n",
+        "Day", "Step", "Answer", "Min", "Median", "Max"
+    );
+    for report in reports {
+        table_row(&mut table, report.day, "Parse", "", &report.parse);
+        for part in &report.parts {
+            let step = match part.part {
+                Part::One => "Part 1",
+                Part::Two => "Part 2",
+            };
+            let answer = part.answer.as_deref().unwrap_or("-");
+            table_row(&mut table, report.day, step, answer, &part.time);
+        }
+    }
+    table
+}
+
+fn json_stats(stats: &Stats) -> String {
+    format!(
+        "\"min_ns\":{},\"median_ns\":{},\"max_ns\":{}",
+        stats.min.as_nanos(),
+        stats.median.as_nanos(),
+        stats.max.as_nanos()
+    )
+}
+
+/// One JSON object per day, with durations in nanoseconds so reports can be compared over time.
+pub fn render_json(reports: &[DayReport]) -> String {
+    let days = reports
+        .iter()
+        .map(|report| {
+            let parts = report
+                .parts
+                .iter()
+                .map(|part| {
+                    let number = match part.part {
+                        Part::One => 1,
+                        Part::Two => 2,
+                    };
+                    let answer = part
+                        .answer
+                        .as_deref()
+                        .map_or(String::from("null"), json::string);
+                    format!(
+                        "{{\"part\":{number},\"answer\":{answer},{}}}",
+                        json_stats(&part.time)
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(",");
+            format!(
+                "{{\"day\":{},\"runs\":{},\"parse\":{{{}}},\"parts\":[{parts}]}}",
+                report.day,
+                report.runs,
+                json_stats(&report.parse)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+    format!("[{days}]")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{
+        bench::{measure, render_json, DayReport, PartReport, Stats},
+        solution::{registry, Part},
+    };
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_stats_from_samples() {
+        assert_eq!(
+            Stats::from_samples(&[ms(5), ms(1), ms(3)]),
+            Some(Stats {
+                min: ms(1),
+                median: ms(3),
+                max: ms(5)
+            })
+        );
+        assert_eq!(
+            Stats::from_samples(&[ms(4), ms(1), ms(2), ms(8)]).map(|s| s.median),
+            Some(ms(3))
+        );
+        assert_eq!(Stats::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn test_render_json() {
+        let stats = Stats {
+            min: ms(1),
+            median: ms(2),
+            max: ms(3),
+        };
+        let report = DayReport {
+            day: 6,
+            runs: 3,
+            parse: stats,
+            parts: vec![
+                PartReport {
+                    part: Part::One,
+                    answer: Some(String::from("288")),
+                    time: stats,
+                },
+                PartReport {
+                    part: Part::Two,
+                    answer: None,
+                    time: stats,
+                },
+            ],
+        };
+
+        assert_eq!(
+            render_json(&[report]),
+            "[{\"day\":6,\"runs\":3,\
+            \"parse\":{\"min_ns\":1000000,\"median_ns\":2000000,\"max_ns\":3000000},\
+            \"parts\":[{\"part\":1,\"answer\":\"288\",\"min_ns\":1000000,\"median_ns\":2000000,\"max_ns\":3000000},\
+            {\"part\":2,\"answer\":null,\"min_ns\":1000000,\"median_ns\":2000000,\"max_ns\":3000000}]}]"
+        );
+    }
+
+    #[test]
+    fn test_measure_day() {
+        let registry = registry();
+        let day = registry.get(6).unwrap();
+        let report = measure(
+            day,
+            "Time:      7  15   30\nDistance:  9  40  200",
+            &Part::ALL,
+            3,
+        )
+        .unwrap();
+
+        assert_eq!(report.runs, 3);
+        assert_eq!(report.parts[0].answer.as_deref(), Some("288"));
+        assert_eq!(report.parts[1].answer.as_deref(), Some("71503"));
+        assert!(report.parts[0].time.min <= report.parts[0].time.max);
+    }
+}
@@ -0,0 +1,660 @@
+use std::{fmt, path::PathBuf};
+
+use advent_of_code_2023::{input::InputSource, solution::Part};
+
+pub const USAGE: &str = "Usage: aoc run [--all | --day <days>] [--part <1|2>] [--input <path>]
+               [--time | --bench <runs> | --explain] [--json] [--vocabulary <name|path>]
+               [--bag <cubes>] [--gears <rule>]
+       aoc verify [--all | --day <days>] [--answers <path>]
+       aoc stats [--input <path>] [--bag <cubes>] [--json]
+       aoc schematic [--input <path>] [--gears <rule>] [--format <auto|ansi|html|svg>]
+
+Commands:
+  run               Solve the selected days (the default command)
+  verify            Check the selected days against the known answers and report PASS, FAIL or
+                    MISSING for every part, failing unless every part passes
+  stats             Report on the day 2 games: per-color draws, the game that needs the largest
+                    bag, the games that are impossible with the bag and a histogram of powers
+  schematic         Draw the day 3 schematic, marking part numbers, numbers that are not parts,
+                    symbols, and gears with the numbers in their ratio
+
+Options:
+  --all           Run every available day (the default)
+  --day <days>    Day to run: a single day (5), a range written like in Rust (3..6 runs
+                  days 3 to 5, 3..=6 also runs day 6) or a comma separated list (1,3,5)
+  --part <1|2>    Run only the given part (both parts run by default)
+  --input <path>  Read the input of a single day from <path>, or from stdin when <path> is -.
+                  Otherwise inputs are read from puzzleN.txt files in the directory set by
+                  AOC_INPUT_DIR or by `input_dir` in aoc.toml (the working directory by default)
+  --time          Print a table with the parse time and the solve time of every part
+  --bench <runs>  Like --time, repeating every day <runs> times and reporting min/median/max
+  --explain       Show how the calibration value of every line of day 1 was found: the first
+                  and last digit, the token they came from and its column
+  --json          Print the timing report or the explanation as JSON
+  --vocabulary <name|path>
+                  Digit words for day 1 part two: english (the default), english-with-zero,
+                  spanish, german, or a file with one `word = digit` entry per line
+  --bag <cubes>   Cubes in the bag for day 2 part one, written like a set of cubes in a game
+                  (\"12 red, 13 green, 14 blue\" by default). Other colors are not in the bag
+  --gears <rule>  Gears for day 3 part two, as comma separated settings: symbols=<chars>,
+                  numbers=<count or range like 2.., 1..4 or 1..=3> and ratio=product|sum
+                  (\"symbols=*, numbers=2, ratio=product\" by default)
+  --format <auto|ansi|html|svg>
+                  Output of the schematic command: auto (the default) prints colored text to a
+                  terminal and plain text when stdout is not a terminal or NO_COLOR is set, ansi
+                  always prints colored text, html an HTML page and svg an SVG image
+  --answers <path>  Known answers to verify against (answers.toml next to the inputs by default)
+  -h, --help      Print this message";
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct RunOptions {
+    pub days: Vec<u8>,
+    pub parts: Vec<Part>,
+    /// Explicit input for a single day, overriding the inputs directory.
+    pub input: Option<InputSource>,
+    /// Number of timed runs per day when a timing report was requested.
+    pub bench: Option<usize>,
+    pub json: bool,
+    /// Trace day 1 line by line instead of only printing the answers.
+    pub explain: bool,
+    /// Built-in vocabulary name or word list file for day 1.
+    pub vocabulary: Option<String>,
+    /// Bag for day 2, like "12 red, 13 green, 14 blue".
+    pub bag: Option<String>,
+    /// Gear rule for day 3, like "symbols=*, numbers=2, ratio=product".
+    pub gears: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct VerifyOptions {
+    pub days: Vec<u8>,
+    pub answers: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct StatsOptions {
+    /// Explicit day 2 input, overriding the inputs directory.
+    pub input: Option<InputSource>,
+    pub bag: Option<String>,
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchematicFormat {
+    /// Colored text when stdout is a terminal and NO_COLOR is not set, plain text otherwise.
+    #[default]
+    Auto,
+    Ansi,
+    Html,
+    Svg,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SchematicOptions {
+    /// Explicit day 3 input, overriding the inputs directory.
+    pub input: Option<InputSource>,
+    pub gears: Option<String>,
+    pub format: SchematicFormat,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Run(RunOptions),
+    Verify(VerifyOptions),
+    Stats(StatsOptions),
+    Schematic(SchematicOptions),
+    Help,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CliError(String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+fn parse_day_number(value: &str) -> Result<u8, CliError> {
+    value
+        .trim()
+        .parse::<u8>()
+        .map_err(|_| CliError(format!("Invalid day '{value}'")))
+}
+
+fn available_day(day: u8, available: &[u8]) -> Result<u8, CliError> {
+    if available.contains(&day) {
+        Ok(day)
+    } else {
+        Err(CliError(format!("Day {day} is not available")))
+    }
+}
+
+fn parse_day(value: &str, available: &[u8]) -> Result<u8, CliError> {
+    available_day(parse_day_number(value)?, available)
+}
+
+// Accepts "5", "3..6", "3..=6" and "1,3,5". Ranges follow Rust syntax: "3..6" runs days 3, 4
+// and 5, while "3..=6" also runs day 6.
+pub fn parse_day_selection(value: &str, available: &[u8]) -> Result<Vec<u8>, CliError> {
+    let mut days = vec![];
+    for item in value.split(',') {
+        if let Some((start, end)) = item.split_once("..") {
+            let empty = || CliError(format!("Empty day range '{item}'"));
+            let start = parse_day(start, available)?;
+            let last = match end.strip_prefix('=') {
+                Some(end) => parse_day(end, available)?,
+                None => {
+                    let end = parse_day_number(end)?;
+                    if end <= start {
+                        return Err(empty());
+                    }
+                    available_day(end - 1, available)?
+                }
+            };
+            if start > last {
+                return Err(empty());
+            }
+            days.extend(available.iter().filter(|day| (start..=last).contains(day)));
+        } else {
+            days.push(parse_day(item, available)?);
+        }
+    }
+    days.sort();
+    days.dedup();
+    Ok(days)
+}
+
+pub fn parse_part(value: &str) -> Result<Part, CliError> {
+    match value.trim() {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        _ => Err(CliError(format!("Invalid part '{value}', expected 1 or 2"))),
+    }
+}
+
+fn parse_run_options<I: Iterator<Item = String>>(
+    mut args: I,
+    available: &[u8],
+) -> Result<RunOptions, CliError> {
+    let mut days: Option<Vec<u8>> = None;
+    let mut parts = Part::ALL.to_vec();
+    let mut input = None;
+    let mut bench = None;
+    let mut json = false;
+    let mut explain = false;
+    let mut vocabulary = None;
+    let mut bag = None;
+    let mut gears = None;
+
+    while let Some(arg) = args.next() {
+        let mut value_for = |flag: &str| {
+            args.next()
+                .ok_or_else(|| CliError(format!("Missing value for {flag}")))
+        };
+        match arg.as_str() {
+            "--all" => days = Some(available.to_vec()),
+            "--day" | "-d" => days = Some(parse_day_selection(&value_for(&arg)?, available)?),
+            "--part" | "-p" => parts = vec![parse_part(&value_for(&arg)?)?],
+            "--input" | "-i" => {
+                input = match value_for(&arg)?.as_str() {
+                    "-" => Some(InputSource::Stdin),
+                    path => Some(InputSource::File(PathBuf::from(path))),
+                }
+            }
+            "--time" => bench = Some(1),
+            "--bench" => {
+                let runs = value_for(&arg)?;
+                bench = match runs.parse::<usize>() {
+                    Ok(runs) if runs > 0 => Some(runs),
+                    _ => return Err(CliError(format!("Invalid number of runs '{runs}'"))),
+                }
+            }
+            "--json" => json = true,
+            "--explain" => explain = true,
+            "--vocabulary" => vocabulary = Some(value_for(&arg)?),
+            "--bag" => bag = Some(value_for(&arg)?),
+            "--gears" => gears = Some(value_for(&arg)?),
+            _ => return Err(CliError(format!("Unknown argument '{arg}'"))),
+        }
+    }
+
+    if explain {
+        if bench.is_some() {
+            return Err(CliError(String::from(
+                "--explain can't be combined with --time or --bench",
+            )));
+        }
+        if days.as_ref().is_some_and(|days| days != &[1]) {
+            return Err(CliError(String::from("--explain only supports day 1")));
+        }
+        days = Some(vec![1]);
+    }
+
+    let days = days.unwrap_or_else(|| available.to_vec());
+    if input.is_some() && days.len() != 1 {
+        return Err(CliError(String::from(
+            "--input needs exactly one day selected with --day",
+        )));
+    }
+    // Flags that configure a single part are refused rather than ignored when it doesn't run
+    for (flag, value, day, part) in [
+        ("--vocabulary", &vocabulary, 1, Part::Two),
+        ("--bag", &bag, 2, Part::One),
+        ("--gears", &gears, 3, Part::Two),
+    ] {
+        if value.is_some() && !(days.contains(&day) && parts.contains(&part)) {
+            return Err(CliError(format!(
+                "{flag} only applies to day {day} {}, which is not selected",
+                part.to_string().to_lowercase()
+            )));
+        }
+    }
+
+    Ok(RunOptions {
+        days,
+        parts,
+        input,
+        bench: bench.or((json && !explain).then_some(1)),
+        json,
+        explain,
+        vocabulary,
+        bag,
+        gears,
+    })
+}
+
+fn parse_verify_options<I: Iterator<Item = String>>(
+    mut args: I,
+    available: &[u8],
+) -> Result<VerifyOptions, CliError> {
+    let mut days: Option<Vec<u8>> = None;
+    let mut answers = None;
+
+    while let Some(arg) = args.next() {
+        let mut value_for = |flag: &str| {
+            args.next()
+                .ok_or_else(|| CliError(format!("Missing value for {flag}")))
+        };
+        match arg.as_str() {
+            "--all" => days = Some(available.to_vec()),
+            "--day" | "-d" => days = Some(parse_day_selection(&value_for(&arg)?, available)?),
+            "--answers" => answers = Some(PathBuf::from(value_for(&arg)?)),
+            _ => return Err(CliError(format!("Unknown argument '{arg}'"))),
+        }
+    }
+
+    Ok(VerifyOptions {
+        days: days.unwrap_or_else(|| available.to_vec()),
+        answers,
+    })
+}
+
+fn parse_stats_options<I: Iterator<Item = String>>(mut args: I) -> Result<StatsOptions, CliError> {
+    let mut input = None;
+    let mut bag = None;
+    let mut json = false;
+
+    while let Some(arg) = args.next() {
+        let mut value_for = |flag: &str| {
+            args.next()
+                .ok_or_else(|| CliError(format!("Missing value for {flag}")))
+        };
+        match arg.as_str() {
+            "--input" | "-i" => {
+                input = match value_for(&arg)?.as_str() {
+                    "-" => Some(InputSource::Stdin),
+                    path => Some(InputSource::File(PathBuf::from(path))),
+                }
+            }
+            "--bag" => bag = Some(value_for(&arg)?),
+            "--json" => json = true,
+            _ => return Err(CliError(format!("Unknown argument '{arg}'"))),
+        }
+    }
+
+    Ok(StatsOptions { input, bag, json })
+}
+
+fn parse_schematic_options<I: Iterator<Item = String>>(
+    mut args: I,
+) -> Result<SchematicOptions, CliError> {
+    let mut input = None;
+    let mut gears = None;
+    let mut format = SchematicFormat::default();
+
+    while let Some(arg) = args.next() {
+        let mut value_for = |flag: &str| {
+            args.next()
+                .ok_or_else(|| CliError(format!("Missing value for {flag}")))
+        };
+        match arg.as_str() {
+            "--input" | "-i" => {
+                input = match value_for(&arg)?.as_str() {
+                    "-" => Some(InputSource::Stdin),
+                    path => Some(InputSource::File(PathBuf::from(path))),
+                }
+            }
+            "--gears" => gears = Some(value_for(&arg)?),
+            "--format" => {
+                format = match value_for(&arg)?.as_str() {
+                    "auto" => SchematicFormat::Auto,
+                    "ansi" => SchematicFormat::Ansi,
+                    "html" => SchematicFormat::Html,
+                    "svg" => SchematicFormat::Svg,
+                    other => return Err(CliError(format!("Unknown format '{other}'"))),
+                }
+            }
+            _ => return Err(CliError(format!("Unknown argument '{arg}'"))),
+        }
+    }
+
+    Ok(SchematicOptions {
+        input,
+        gears,
+        format,
+    })
+}
+
+pub fn parse_args<I: IntoIterator<Item = String>>(
+    args: I,
+    available: &[u8],
+) -> Result<Command, CliError> {
+    let mut args = args.into_iter().peekable();
+
+    if args
+        .peek()
+        .is_some_and(|arg| arg == "-h" || arg == "--help" || arg == "help")
+    {
+        return Ok(Command::Help);
+    }
+
+    match args.peek().map(|arg| arg.as_str()) {
+        Some("run") => {
+            args.next();
+            parse_run_options(args, available).map(Command::Run)
+        }
+        Some("verify") => {
+            args.next();
+            parse_verify_options(args, available).map(Command::Verify)
+        }
+        Some("stats") => {
+            args.next();
+            parse_stats_options(args).map(Command::Stats)
+        }
+        Some("schematic") => {
+            args.next();
+            parse_schematic_options(args).map(Command::Schematic)
+        }
+        // Running without a subcommand behaves like `aoc run`
+        None
+        | Some(
+            "--all" | "--day" | "-d" | "--part" | "-p" | "--input" | "-i" | "--time" | "--bench"
+            | "--json" | "--explain" | "--vocabulary" | "--bag" | "--gears",
+        ) => parse_run_options(args, available).map(Command::Run),
+        Some(other) => Err(CliError(format!("Unknown command '{other}'"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use advent_of_code_2023::{input::InputSource, solution::Part};
+
+    use crate::cli::{
+        parse_args, parse_day_selection, CliError, Command, RunOptions, SchematicFormat,
+        SchematicOptions, StatsOptions, VerifyOptions,
+    };
+
+    const DAYS: [u8; 6] = [1, 2, 3, 4, 5, 6];
+
+    fn args(input: &str) -> Vec<String> {
+        input.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_day_selection() {
+        assert_eq!(parse_day_selection("5", &DAYS), Ok(vec![5]));
+        assert_eq!(parse_day_selection("3..6", &DAYS), Ok(vec![3, 4, 5]));
+        assert_eq!(parse_day_selection("3..=6", &DAYS), Ok(vec![3, 4, 5, 6]));
+        assert_eq!(parse_day_selection("1..7", &DAYS), Ok(DAYS.to_vec()));
+        assert!(parse_day_selection("3..3", &DAYS).is_err());
+        assert!(parse_day_selection("3..8", &DAYS).is_err());
+        assert_eq!(parse_day_selection("5,1,5", &DAYS), Ok(vec![1, 5]));
+        assert!(parse_day_selection("6..3", &DAYS).is_err());
+        assert!(parse_day_selection("9", &DAYS).is_err());
+        assert!(parse_day_selection("x", &DAYS).is_err());
+    }
+
+    #[test]
+    fn test_parse_run_command() {
+        assert_eq!(
+            parse_args(args("run --day 5 --part 1"), &DAYS),
+            Ok(Command::Run(RunOptions {
+                days: vec![5],
+                parts: vec![Part::One],
+                input: None,
+                bench: None,
+                json: false,
+                explain: false,
+                vocabulary: None,
+                bag: None,
+                gears: None
+            }))
+        );
+        assert_eq!(
+            parse_args(args("run --all"), &DAYS),
+            Ok(Command::Run(RunOptions {
+                days: DAYS.to_vec(),
+                parts: vec![Part::One, Part::Two],
+                input: None,
+                bench: None,
+                json: false,
+                explain: false,
+                vocabulary: None,
+                bag: None,
+                gears: None
+            }))
+        );
+        assert_eq!(
+            parse_args(args(""), &DAYS),
+            parse_args(args("run --all"), &DAYS)
+        );
+    }
+
+    #[test]
+    fn test_parse_input_argument() {
+        assert_eq!(
+            parse_args(args("run --day 4 --input /tmp/cards.txt"), &DAYS),
+            Ok(Command::Run(RunOptions {
+                days: vec![4],
+                parts: vec![Part::One, Part::Two],
+                input: Some(InputSource::File(PathBuf::from("/tmp/cards.txt"))),
+                bench: None,
+                json: false,
+                explain: false,
+                vocabulary: None,
+                bag: None,
+                gears: None
+            }))
+        );
+        assert_eq!(
+            parse_args(args("--day 2 --part 2 --input -"), &DAYS),
+            Ok(Command::Run(RunOptions {
+                days: vec![2],
+                parts: vec![Part::Two],
+                input: Some(InputSource::Stdin),
+                bench: None,
+                json: false,
+                explain: false,
+                vocabulary: None,
+                bag: None,
+                gears: None
+            }))
+        );
+        assert!(parse_args(args("run --input -"), &DAYS).is_err());
+        assert!(parse_args(args("run --day 1..=2 --input -"), &DAYS).is_err());
+    }
+
+    #[test]
+    fn test_parse_timing_arguments() {
+        let Ok(Command::Run(options)) = parse_args(args("run --day 5 --bench 10"), &DAYS) else {
+            panic!("Expected a run command");
+        };
+        assert_eq!((options.bench, options.json), (Some(10), false));
+
+        let Ok(Command::Run(options)) = parse_args(args("--json"), &DAYS) else {
+            panic!("Expected a run command");
+        };
+        assert_eq!((options.bench, options.json), (Some(1), true));
+
+        let Ok(Command::Run(options)) = parse_args(args("--time"), &DAYS) else {
+            panic!("Expected a run command");
+        };
+        assert_eq!(options.bench, Some(1));
+
+        assert!(parse_args(args("run --bench 0"), &DAYS).is_err());
+        assert!(parse_args(args("run --bench many"), &DAYS).is_err());
+    }
+
+    #[test]
+    fn test_parse_vocabulary_argument() {
+        let Ok(Command::Run(options)) = parse_args(args("--day 1 --vocabulary german"), &DAYS)
+        else {
+            panic!("Expected a run command");
+        };
+        assert_eq!(options.vocabulary.as_deref(), Some("german"));
+        assert!(parse_args(args("run --vocabulary"), &DAYS).is_err());
+    }
+
+    #[test]
+    fn test_parse_bag_argument() {
+        let arguments = vec![
+            String::from("--day"),
+            String::from("2"),
+            String::from("--bag"),
+            String::from("20 red, 13 green, 15 blue"),
+        ];
+        let Ok(Command::Run(options)) = parse_args(arguments, &DAYS) else {
+            panic!("Expected a run command");
+        };
+        assert_eq!(options.bag.as_deref(), Some("20 red, 13 green, 15 blue"));
+        assert!(parse_args(args("run --bag"), &DAYS).is_err());
+
+        let Ok(Command::Run(options)) = parse_args(args("--day 3 --gears numbers=1.."), &DAYS)
+        else {
+            panic!("Expected a run command");
+        };
+        assert_eq!(options.gears.as_deref(), Some("numbers=1.."));
+    }
+
+    #[test]
+    fn test_parse_explain_argument() {
+        let Ok(Command::Run(options)) = parse_args(args("--explain --json"), &DAYS) else {
+            panic!("Expected a run command");
+        };
+        assert_eq!(options.days, vec![1]);
+        assert_eq!(
+            (options.explain, options.json, options.bench),
+            (true, true, None)
+        );
+
+        assert!(parse_args(args("run --day 1 --explain"), &DAYS).is_ok());
+        assert!(parse_args(args("run --day 2 --explain"), &DAYS).is_err());
+        assert!(parse_args(args("run --explain --time"), &DAYS).is_err());
+    }
+
+    #[test]
+    fn test_parse_verify_command() {
+        assert_eq!(
+            parse_args(args("verify"), &DAYS),
+            Ok(Command::Verify(VerifyOptions {
+                days: DAYS.to_vec(),
+                answers: None
+            }))
+        );
+        assert_eq!(
+            parse_args(args("verify --day 3..=4 --answers known.toml"), &DAYS),
+            Ok(Command::Verify(VerifyOptions {
+                days: vec![3, 4],
+                answers: Some(PathBuf::from("known.toml"))
+            }))
+        );
+        assert!(parse_args(args("verify --part 1"), &DAYS).is_err());
+    }
+
+    #[test]
+    fn test_parse_stats_command() {
+        assert_eq!(
+            parse_args(args("stats --json --input games.txt"), &DAYS),
+            Ok(Command::Stats(StatsOptions {
+                input: Some(InputSource::File(PathBuf::from("games.txt"))),
+                bag: None,
+                json: true
+            }))
+        );
+        assert!(parse_args(args("stats --day 2"), &DAYS).is_err());
+    }
+
+    #[test]
+    fn test_parse_schematic_command() {
+        assert_eq!(
+            parse_args(args("schematic"), &DAYS),
+            Ok(Command::Schematic(SchematicOptions {
+                input: None,
+                gears: None,
+                format: SchematicFormat::Auto
+            }))
+        );
+        assert!(matches!(
+            parse_args(args("schematic --format ansi"), &DAYS),
+            Ok(Command::Schematic(SchematicOptions {
+                format: SchematicFormat::Ansi,
+                ..
+            }))
+        ));
+        assert_eq!(
+            parse_args(
+                args("schematic --format svg --input - --gears numbers=1.."),
+                &DAYS
+            ),
+            Ok(Command::Schematic(SchematicOptions {
+                input: Some(InputSource::Stdin),
+                gears: Some(String::from("numbers=1..")),
+                format: SchematicFormat::Svg
+            }))
+        );
+        assert!(parse_args(args("schematic --format png"), &DAYS).is_err());
+        assert!(parse_args(args("schematic --json"), &DAYS).is_err());
+    }
+
+    #[test]
+    fn test_parse_flags_for_unselected_days() {
+        assert!(parse_args(args("run --day 1 --vocabulary german"), &DAYS).is_ok());
+        assert_eq!(
+            parse_args(args("run --day 2 --vocabulary german"), &DAYS),
+            Err(CliError(String::from(
+                "--vocabulary only applies to day 1 part two, which is not selected"
+            )))
+        );
+        assert!(parse_args(args("--day 1 --part 1 --vocabulary german"), &DAYS).is_err());
+        assert!(parse_args(args("--day 2 --part 1 --bag cubes"), &DAYS).is_ok());
+        assert!(parse_args(args("--day 2 --part 2 --bag cubes"), &DAYS).is_err());
+        assert!(parse_args(args("--day 3 --gears numbers=1.."), &DAYS).is_ok());
+        assert_eq!(
+            parse_args(args("--day 1..=2 --gears numbers=1.."), &DAYS),
+            Err(CliError(String::from(
+                "--gears only applies to day 3 part two, which is not selected"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_arguments() {
+        assert!(parse_args(args("run --part 3"), &DAYS).is_err());
+        assert!(parse_args(args("run --day"), &DAYS).is_err());
+        assert!(parse_args(args("jump"), &DAYS).is_err());
+        assert_eq!(parse_args(args("--help"), &DAYS), Ok(Command::Help));
+    }
+}
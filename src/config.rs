@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Location, Result};
+
+// A small reader for the subset of TOML used by our configuration files: `[section]` headers,
+// `key = value` pairs with quoted or bare values, and `#` comments. Keys inside a section are
+// stored as "section.key".
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (idx, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..idx],
+            _ => {}
+        }
+    }
+    line
+}
+
+pub fn parse_key_values(input: &str) -> Result<BTreeMap<String, String>> {
+    let mut values = BTreeMap::new();
+    let mut section: Option<String> = None;
+
+    for (idx, raw_line) in input.lines().enumerate() {
+        let line = strip_comment(raw_line).trim();
+        let error = || {
+            Error::malformed(
+                "a `key = value` entry",
+                raw_line.trim(),
+                Location::default(),
+            )
+            .at_line(idx + 1)
+        };
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            if name.trim().is_empty() {
+                return Err(error());
+            }
+            section = Some(name.trim().to_string());
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(error());
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if key.is_empty() || value.is_empty() {
+            return Err(error());
+        }
+
+        let value = match value.strip_prefix('"') {
+            Some(quoted) => quoted.strip_suffix('"').ok_or_else(error)?,
+            None => value,
+        };
+        let key = match &section {
+            Some(section) => format!("{section}.{key}"),
+            None => key.to_string(),
+        };
+        values.insert(key, value.to_string());
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config::parse_key_values, error::Error};
+
+    #[test]
+    fn test_parse_key_values() {
+        let input = r#"
+# Where the puzzle inputs live
+input_dir = "inputs" # relative to this file
+
+[day1]
+part1 = 142
+"#;
+        let values = parse_key_values(input).unwrap();
+
+        assert_eq!(values.get("input_dir").map(String::as_str), Some("inputs"));
+        assert_eq!(values.get("day1.part1").map(String::as_str), Some("142"));
+        assert_eq!(values.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_invalid_entry() {
+        assert_eq!(
+            parse_key_values("a = 1\nnot an entry")
+                .unwrap_err()
+                .to_string(),
+            "2: expected a `key = value` entry, found 'not an entry'"
+        );
+        assert!(matches!(
+            parse_key_values("[]"),
+            Err(Error::Malformed { .. })
+        ));
+        assert!(parse_key_values("path = \"unterminated").is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use std::{fmt, io};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Position of an error in the puzzle input. Lines and columns are 1-based and unknown until a
+/// caller that knows them fills them in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+impl Location {
+    pub fn column(column: usize) -> Self {
+        Location {
+            line: None,
+            column: Some(column),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{line}:{column}: "),
+            (Some(line), None) => write!(f, "{line}: "),
+            (None, Some(column)) => write!(f, "column {column}: "),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// A token that should have been a number.
+    BadNumber { text: String, location: Location },
+    /// A cube color that is not known.
+    UnknownColor { text: String, location: Location },
+    /// A cube color that appears more than once in the same set of cubes.
+    DuplicateColor { text: String, location: Location },
+    /// Text that does not have the expected shape.
+    Malformed {
+        expected: &'static str,
+        text: String,
+        location: Location,
+    },
+    /// A required piece of the input is not there at all.
+    Missing {
+        expected: &'static str,
+        location: Location,
+    },
+    /// The input is well formed but has no answer.
+    NoSolution(&'static str),
+    /// The input could not be read.
+    Io {
+        source_name: String,
+        error: io::Error,
+    },
+    /// A configuration file could not be parsed.
+    Config {
+        source_name: String,
+        error: Box<Error>,
+    },
+}
+
+impl Error {
+    pub fn bad_number(text: &str, location: Location) -> Self {
+        Error::BadNumber {
+            text: text.to_string(),
+            location,
+        }
+    }
+
+    pub fn malformed(expected: &'static str, text: &str, location: Location) -> Self {
+        Error::Malformed {
+            expected,
+            text: text.to_string(),
+            location,
+        }
+    }
+
+    pub fn missing(expected: &'static str, location: Location) -> Self {
+        Error::Missing { expected, location }
+    }
+
+    pub fn location(&self) -> Option<Location> {
+        match self {
+            Error::BadNumber { location, .. }
+            | Error::UnknownColor { location, .. }
+            | Error::DuplicateColor { location, .. }
+            | Error::Malformed { location, .. }
+            | Error::Missing { location, .. } => Some(*location),
+            Error::NoSolution(_) | Error::Io { .. } | Error::Config { .. } => None,
+        }
+    }
+
+    fn location_mut(&mut self) -> Option<&mut Location> {
+        match self {
+            Error::BadNumber { location, .. }
+            | Error::UnknownColor { location, .. }
+            | Error::DuplicateColor { location, .. }
+            | Error::Malformed { location, .. }
+            | Error::Missing { location, .. } => Some(location),
+            Error::NoSolution(_) | Error::Io { .. } | Error::Config { .. } => None,
+        }
+    }
+
+    /// Records the line the error happened on, unless a more precise line is already known.
+    pub fn at_line(mut self, line: usize) -> Self {
+        if let Some(location) = self.location_mut() {
+            location.line.get_or_insert(line);
+        }
+        self
+    }
+
+    /// Shifts a line relative to a block of the input to a line relative to the whole input.
+    pub fn offset_lines(mut self, offset: usize) -> Self {
+        if let Some(line) = self.location_mut().and_then(|l| l.line.as_mut()) {
+            *line += offset;
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::BadNumber { text, location } => write!(f, "{location}bad number '{text}'"),
+            Error::UnknownColor { text, location } => {
+                write!(f, "{location}unknown cube color '{text}'")
+            }
+            Error::DuplicateColor { text, location } => {
+                write!(f, "{location}cube color '{text}' drawn twice in one set")
+            }
+            Error::Malformed {
+                expected,
+                text,
+                location,
+            } => write!(f, "{location}expected {expected}, found '{text}'"),
+            Error::Missing { expected, location } => write!(f, "{location}missing {expected}"),
+            Error::NoSolution(reason) => write!(f, "no solution: {reason}"),
+            Error::Io { source_name, error } => write!(f, "{source_name}: {error}"),
+            Error::Config { source_name, error } => match error.location() {
+                Some(Location { line: Some(_), .. }) => write!(f, "{source_name}:{error}"),
+                _ => write!(f, "{source_name}: {error}"),
+            },
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { error, .. } => Some(error),
+            Error::Config { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// 1-based column of `part` inside `line`, when `part` is a subslice of `line`.
+pub fn column_of(line: &str, part: &str) -> Option<usize> {
+    let start = (part.as_ptr() as usize).checked_sub(line.as_ptr() as usize)?;
+    (start + part.len() <= line.len()).then_some(start + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{column_of, Error, Location};
+
+    #[test]
+    fn test_error_display() {
+        let error = Error::bad_number("x3", Location::column(9)).at_line(17);
+        assert_eq!(error.to_string(), "17:9: bad number 'x3'");
+
+        let error = Error::missing("seeds", Location::default()).at_line(1);
+        assert_eq!(error.to_string(), "1: missing seeds");
+
+        let error = Error::Config {
+            source_name: String::from("aoc.toml"),
+            error: Box::new(error),
+        };
+        assert_eq!(error.to_string(), "aoc.toml:1: missing seeds");
+    }
+
+    #[test]
+    fn test_error_line_offsets() {
+        let error = Error::bad_number("x", Location::default())
+            .at_line(2)
+            .at_line(5)
+            .offset_lines(10);
+
+        assert_eq!(error.location().and_then(|l| l.line), Some(12));
+    }
+
+    #[test]
+    fn test_column_of() {
+        let line = String::from("Card 1: 41 x3");
+        let token = line.split_whitespace().last().unwrap();
+
+        assert_eq!(column_of(&line, token), Some(12));
+        assert_eq!(column_of(&line[..4], token), None);
+    }
+}
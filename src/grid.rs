@@ -0,0 +1,267 @@
+use std::{fmt, ops::Index};
+
+use crate::error::{Error, Location, Result};
+
+/// Position of a cell. Rows grow downwards and columns to the right, both starting at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl Point {
+    pub fn new(row: usize, column: usize) -> Self {
+        Point { row, column }
+    }
+
+    /// The point `(rows, columns)` away, or `None` when it would be above or left of the origin.
+    pub fn offset(self, (rows, columns): (isize, isize)) -> Option<Point> {
+        Some(Point {
+            row: self.row.checked_add_signed(rows)?,
+            column: self.column.checked_add_signed(columns)?,
+        })
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.column)
+    }
+}
+
+/// Offsets of the cells sharing a side with a cell.
+pub const ORTHOGONAL: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+
+/// Offsets of the cells sharing a side or a corner with a cell.
+pub const SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// A rectangular grid of cells stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid with one row per line of `input`, turning every character into a cell.
+    /// All lines must have the same number of characters.
+    pub fn parse(input: &str, mut cell: impl FnMut(Point, char) -> Result<T>) -> Result<Self> {
+        let mut width = None;
+        let mut cells = vec![];
+        let mut height = 0;
+
+        for (row, line) in input.lines().enumerate() {
+            let start = cells.len();
+            for (column, c) in line.chars().enumerate() {
+                let value = cell(Point::new(row, column), c).map_err(|e| e.at_line(row + 1))?;
+                cells.push(value);
+            }
+            let row_width = cells.len() - start;
+            if *width.get_or_insert(row_width) != row_width {
+                return Err(
+                    Error::malformed("rows of the same width", line, Location::default())
+                        .at_line(row + 1),
+                );
+            }
+            height += 1;
+        }
+
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.row < self.height && point.column < self.width
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.contains(point)
+            .then(|| &self.cells[point.row * self.width + point.column])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.contains(point)
+            .then(|| &mut self.cells[point.row * self.width + point.column])
+    }
+
+    pub fn row(&self, row: usize) -> Option<&[T]> {
+        (row < self.height).then(|| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    pub fn column(&self, column: usize) -> Option<impl Iterator<Item = &T>> {
+        (column < self.width).then(|| self.cells.iter().skip(column).step_by(self.width))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).filter_map(|row| self.row(row))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().enumerate().map(|(idx, cell)| {
+            let point = Point::new(idx / self.width, idx % self.width);
+            (point, cell)
+        })
+    }
+
+    /// The points at the given offsets from `point` that are inside the grid.
+    pub fn neighbours<'a>(
+        &'a self,
+        point: Point,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Point> + 'a {
+        offsets
+            .iter()
+            .filter_map(move |&offset| point.offset(offset))
+            .filter(|&neighbour| self.contains(neighbour))
+    }
+
+    /// Neighbours sharing a side with `point`.
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(point, &ORTHOGONAL)
+    }
+
+    /// Neighbours sharing a side or a corner with `point`.
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        self.neighbours(point, &SURROUNDING)
+    }
+}
+
+impl Grid<char> {
+    pub fn parse_chars(input: &str) -> Result<Self> {
+        Grid::parse(input, |_, c| Ok(c))
+    }
+
+    /// Like [`Grid::parse_chars`], but rows shorter than the longest one are padded with `fill`
+    /// instead of being rejected.
+    pub fn parse_padded(input: &str, fill: char) -> Self {
+        let width = input.lines().map(|line| line.chars().count()).max();
+        let mut grid = Grid::new(width.unwrap_or(0), input.lines().count(), fill);
+        for (row, line) in input.lines().enumerate() {
+            for (column, c) in line.chars().enumerate() {
+                if let Some(cell) = grid.get_mut(Point::new(row, column)) {
+                    *cell = c;
+                }
+            }
+        }
+        grid
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{point} is outside the grid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::{Error, Location},
+        grid::{Grid, Point},
+    };
+
+    const INPUT: &str = "abc\ndef";
+
+    #[test]
+    fn test_parse_grid() {
+        let grid = Grid::parse_chars(INPUT).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point::new(1, 2)], 'f');
+        assert_eq!(grid.get(Point::new(2, 0)), None);
+        assert_eq!(grid.row(1), Some(&['d', 'e', 'f'][..]));
+        assert_eq!(grid.column(1).unwrap().collect::<String>(), "be");
+        assert!(grid.column(3).is_none());
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.iter().nth(4), Some((Point::new(1, 1), &'e')));
+    }
+
+    #[test]
+    fn test_parse_grid_errors() {
+        assert_eq!(
+            Grid::parse_chars("abc\nde").unwrap_err().to_string(),
+            "2: expected rows of the same width, found 'de'"
+        );
+
+        let error = Grid::parse("12\n3x", |point, c| {
+            c.to_digit(10).ok_or_else(|| {
+                Error::bad_number(&c.to_string(), Location::column(point.column + 1))
+            })
+        })
+        .unwrap_err();
+        assert_eq!(error.to_string(), "2:2: bad number 'x'");
+    }
+
+    #[test]
+    fn test_parse_padded_grid() {
+        let grid = Grid::parse_padded(
+            "abc
+d
+", '.',
+        );
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.row(1), Some(&['d', '.', '.'][..]));
+        assert_eq!(Grid::parse_padded("", '.').width(), 0);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 3, 0);
+        let corner = Point::new(0, 0);
+        let center = Point::new(1, 1);
+
+        assert_eq!(
+            grid.neighbours4(corner).collect::<Vec<_>>(),
+            vec![Point::new(0, 1), Point::new(1, 0)]
+        );
+        assert_eq!(grid.neighbours8(corner).count(), 3);
+        assert_eq!(grid.neighbours8(center).count(), 8);
+        assert_eq!(grid.neighbours4(Point::new(2, 1)).count(), 3);
+        assert_eq!(corner.offset((-1, 0)), None);
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut grid = Grid::new(2, 2, '.');
+        *grid.get_mut(Point::new(1, 0)).unwrap() = '#';
+
+        assert_eq!(grid.column(0).unwrap().collect::<String>(), ".#");
+        assert!(grid.get_mut(Point::new(0, 2)).is_none());
+    }
+}
@@ -0,0 +1,144 @@
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    config,
+    error::{Error, Result},
+};
+
+/// Environment variable overriding the directory that holds the `puzzleN.txt` files.
+pub const INPUT_DIR_VAR: &str = "AOC_INPUT_DIR";
+
+/// Configuration file looked up in the working directory and then in `~/.config/aoc/`.
+pub const CONFIG_FILE: &str = "aoc.toml";
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum InputSource {
+    /// An explicit input file for a single day.
+    File(PathBuf),
+    Stdin,
+    /// A directory with one `puzzleN.txt` file per day.
+    Directory(PathBuf),
+}
+
+pub fn input_file_name(day: u8) -> String {
+    format!("puzzle{day}.txt")
+}
+
+impl InputSource {
+    pub fn name(&self, day: u8) -> String {
+        match self {
+            InputSource::File(path) => path.display().to_string(),
+            InputSource::Stdin => String::from("<stdin>"),
+            InputSource::Directory(dir) if dir == Path::new(".") => input_file_name(day),
+            InputSource::Directory(dir) => dir.join(input_file_name(day)).display().to_string(),
+        }
+    }
+
+    pub fn read(&self, day: u8) -> Result<String> {
+        let result = match self {
+            InputSource::File(path) => fs::read_to_string(path),
+            InputSource::Stdin => {
+                let mut input = String::new();
+                io::stdin().read_to_string(&mut input).map(|_| input)
+            }
+            InputSource::Directory(dir) => fs::read_to_string(dir.join(input_file_name(day))),
+        };
+        result.map_err(|error| Error::Io {
+            source_name: self.name(day),
+            error,
+        })
+    }
+}
+
+// A relative `input_dir` in a configuration file is resolved against the file's own directory,
+// so the same configuration works from any working directory.
+fn input_dir_from_config(config_path: &Path, contents: &str) -> Result<Option<PathBuf>> {
+    let values = config::parse_key_values(contents)?;
+    let Some(dir) = values.get("input_dir").map(PathBuf::from) else {
+        return Ok(None);
+    };
+    match config_path.parent() {
+        Some(parent) if dir.is_relative() => Ok(Some(parent.join(dir))),
+        _ => Ok(Some(dir)),
+    }
+}
+
+fn config_paths() -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from(CONFIG_FILE)];
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    if let Some(config_home) = config_home {
+        paths.push(config_home.join("aoc").join(CONFIG_FILE));
+    }
+    paths
+}
+
+/// Resolves the inputs directory: the `AOC_INPUT_DIR` environment variable wins, then the
+/// first configuration file that sets `input_dir`, then the working directory. A configuration
+/// file that exists but can't be parsed is an error rather than being skipped.
+pub fn input_dir() -> Result<PathBuf> {
+    if let Some(dir) = env::var_os(INPUT_DIR_VAR).filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    for path in config_paths() {
+        let Ok(contents) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let dir = input_dir_from_config(&path, &contents).map_err(|error| Error::Config {
+            source_name: path.display().to_string(),
+            error: Box::new(error),
+        })?;
+        if let Some(dir) = dir {
+            return Ok(dir);
+        }
+    }
+    Ok(PathBuf::from("."))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::input::{input_dir_from_config, InputSource};
+
+    #[test]
+    fn test_input_source_name() {
+        let source = InputSource::Directory(PathBuf::from("inputs"));
+
+        assert_eq!(source.name(4), "inputs/puzzle4.txt");
+        assert_eq!(
+            InputSource::Directory(PathBuf::from(".")).name(4),
+            "puzzle4.txt"
+        );
+        assert_eq!(InputSource::Stdin.name(4), "<stdin>");
+    }
+
+    #[test]
+    fn test_input_dir_from_config() {
+        let config_path = Path::new("/home/elf/aoc/aoc.toml");
+
+        assert_eq!(
+            input_dir_from_config(config_path, "input_dir = \"inputs\"").unwrap(),
+            Some(PathBuf::from("/home/elf/aoc/inputs"))
+        );
+        assert_eq!(
+            input_dir_from_config(config_path, "input_dir = \"/srv/aoc\"").unwrap(),
+            Some(PathBuf::from("/srv/aoc"))
+        );
+        assert_eq!(
+            input_dir_from_config(config_path, "other = 1").unwrap(),
+            None
+        );
+        assert_eq!(
+            input_dir_from_config(config_path, "input_dir \"inputs\"")
+                .unwrap_err()
+                .to_string(),
+            "1: expected a `key = value` entry, found 'input_dir \"inputs\"'"
+        );
+    }
+}
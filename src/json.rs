@@ -0,0 +1,29 @@
+use std::fmt::Write;
+
+// Just enough JSON for our reports, so the crate does not need a serialization dependency.
+
+/// `value` as a quoted JSON string.
+pub fn string(value: &str) -> String {
+    let mut output = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            c if c.is_control() => write!(output, "\\u{:04x}", c as u32).unwrap(),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json;
+
+    #[test]
+    fn test_string() {
+        assert_eq!(json::string("a \"b\"\n"), "\"a \\\"b\\\"\\u000a\"");
+        assert_eq!(json::string("fünf\\"), "\"fünf\\\\\"");
+    }
+}
@@ -0,0 +1,21 @@
+//! Advent of Code 2023 solutions.
+//!
+//! Each `puzzleN` module exposes the parsers and solvers for day N, and [`solution::registry`]
+//! lists every solved day behind the common [`solution::Solution`] trait. The `aoc` binary is a
+//! thin command-line front end over this library.
+
+pub mod bench;
+pub mod config;
+pub mod error;
+pub mod grid;
+pub mod input;
+pub mod json;
+pub mod puzzle1;
+pub mod puzzle2;
+pub mod puzzle3;
+pub mod puzzle4;
+pub mod puzzle5;
+pub mod puzzle6;
+pub mod rng;
+pub mod solution;
+pub mod verify;
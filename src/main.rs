@@ -1,31 +1,371 @@
-mod puzzle1;
-mod puzzle2;
-mod puzzle3;
-mod puzzle4;
-mod puzzle5;
-mod puzzle6;
+use std::{
+    env, fs,
+    io::{self, IsTerminal},
+    process,
+};
 
-fn main() {
-    let answer1 = puzzle1::answer();
-    println!("Answer to puzzle #1 (Part two): {answer1}");
+use advent_of_code_2023::{
+    bench,
+    error::{Error, Result},
+    input::{self, InputSource},
+    puzzle1::{self, Puzzle1, Vocabulary},
+    puzzle2::{self, Bag, GameSet, Puzzle2},
+    puzzle3::{self, GearRule, Puzzle3},
+    solution::{self, Part, Registry},
+    verify::{self, Answers, Status},
+};
+use cli::{Command, RunOptions, SchematicFormat, SchematicOptions, StatsOptions, VerifyOptions};
+
+mod cli;
+
+// Parse errors are prefixed with the input name, e.g. "puzzle4.txt:17: bad number 'x3'"
+fn describe(error: &Error, source_name: &str) -> String {
+    match error {
+        Error::Io { .. } | Error::Config { .. } => error.to_string(),
+        _ if error.location().is_some_and(|l| l.line.is_some()) => {
+            format!("{source_name}:{error}")
+        }
+        _ => format!("{source_name}: {error}"),
+    }
+}
+
+// A built-in vocabulary name, or else the path of a word list file
+fn load_vocabulary(name_or_path: &str) -> Result<Vocabulary> {
+    if let Some(vocabulary) = Vocabulary::named(name_or_path) {
+        return Ok(vocabulary);
+    }
+    fs::read_to_string(name_or_path)
+        .map_err(|error| Error::Io {
+            source_name: name_or_path.to_string(),
+            error,
+        })
+        .and_then(|contents| Vocabulary::parse(&contents))
+}
+
+// The explicit --input, or else the inputs directory
+fn input_source(input: &Option<InputSource>) -> Result<InputSource> {
+    match input {
+        Some(source) => Ok(source.clone()),
+        None => input::input_dir().map(InputSource::Directory),
+    }
+}
 
-    let (answer2_1, answer2_2) = puzzle2::answer();
-    println!("Answer to puzzle #2 (Part one): {answer2_1}");
-    println!("Answer to puzzle #2 (Part two): {answer2_2}");
+// Returns false when any of the selected days failed
+fn run(registry: &Registry, options: &RunOptions) -> bool {
+    let source = match input_source(&options.input) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{error}");
+            return false;
+        }
+    };
+    let mut succeeded = true;
+    let mut reports = vec![];
 
-    let (answer3_1, answer3_2) = puzzle3::answer();
-    println!("Answer to puzzle #3 (Part one): {answer3_1}");
-    println!("Answer to puzzle #3 (Part two): {answer3_2}");
+    for day in options
+        .days
+        .iter()
+        .filter_map(|&number| registry.get(number))
+    {
+        let number = day.number;
+        let source_name = source.name(number);
+        if let Some(runs) = options.bench {
+            match source
+                .read(number)
+                .and_then(|input| bench::measure(day, &input, &options.parts, runs))
+            {
+                Ok(report) => reports.push(report),
+                Err(error) => {
+                    eprintln!("{}", describe(&error, &source_name));
+                    succeeded = false;
+                }
+            }
+            continue;
+        }
 
-    let (answer4_1, answer4_2) = puzzle4::answer();
-    println!("Answer to puzzle #4 (Part one): {answer4_1}");
-    println!("Answer to puzzle #4 (Part two): {answer4_2}");
+        let parsed_input = match source.read(number).and_then(|input| day.parse(&input)) {
+            Ok(parsed_input) => parsed_input,
+            Err(error) => {
+                eprintln!("{}", describe(&error, &source_name));
+                succeeded = false;
+                continue;
+            }
+        };
+
+        for &part in &options.parts {
+            match parsed_input.solve(part) {
+                Ok(Some(answer)) => println!("Answer to puzzle #{number} ({part}): {answer}"),
+                Ok(None) => println!("Answer to puzzle #{number} ({part}): not implemented"),
+                Err(error) => {
+                    eprintln!("{}", describe(&error, &source_name));
+                    succeeded = false;
+                }
+            }
+        }
+    }
+
+    if options.json {
+        println!("{}", bench::render_json(&reports));
+    } else if options.bench.is_some() {
+        print!("{}", bench::render_table(&reports));
+    }
+    succeeded
+}
+
+// Traces day 1 line by line. Returns false when the input can't be read or a line has no digit
+fn explain(options: &RunOptions, vocabulary: &Vocabulary) -> bool {
+    let source = match input_source(&options.input) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{error}");
+            return false;
+        }
+    };
+    let input = match source.read(1) {
+        Ok(input) => input,
+        Err(error) => {
+            eprintln!("{}", describe(&error, &source.name(1)));
+            return false;
+        }
+    };
+
+    let traces = options
+        .parts
+        .iter()
+        .map(|&part| {
+            let traces = match part {
+                Part::One => puzzle1::trace(&input, &Vocabulary::digits_only()),
+                Part::Two => puzzle1::trace(&input, vocabulary),
+            };
+            (part, traces)
+        })
+        .collect::<Vec<_>>();
+
+    if options.json {
+        println!("{}", puzzle1::render_trace_json(&traces));
+    } else {
+        print!("{}", puzzle1::render_trace_table(&traces));
+    }
+    traces
+        .iter()
+        .all(|(_, lines)| lines.iter().all(|line| line.value().is_some()))
+}
 
-    let (answer5_1, answer5_2) = puzzle5::answer();
-    println!("Answer to puzzle #5 (Part one): {answer5_1}");
-    println!("Answer to puzzle #5 (Part two): {answer5_2}");
+// Returns false unless every part of the selected days passed
+fn verify(registry: &Registry, options: &VerifyOptions) -> bool {
+    let input_dir = match input::input_dir() {
+        Ok(input_dir) => input_dir,
+        Err(error) => {
+            eprintln!("{error}");
+            return false;
+        }
+    };
+    let answers_path = options
+        .answers
+        .clone()
+        .unwrap_or_else(|| input_dir.join(verify::ANSWERS_FILE));
+    let answers_name = answers_path.display().to_string();
+    let answers = match fs::read_to_string(&answers_path)
+        .map_err(|error| Error::Io {
+            source_name: answers_name.clone(),
+            error,
+        })
+        .and_then(|contents| Answers::parse(&contents))
+    {
+        Ok(answers) => answers,
+        Err(error) => {
+            eprintln!("{}", describe(&error, &answers_name));
+            return false;
+        }
+    };
+
+    let source = InputSource::Directory(input_dir);
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+
+    for day in options
+        .days
+        .iter()
+        .filter_map(|&number| registry.get(number))
+    {
+        let source_name = source.name(day.number);
+        let verdicts = source
+            .read(day.number)
+            .and_then(|input| verify::verify_day(day, &input, &answers));
+        match verdicts {
+            Ok(verdicts) => {
+                for verdict in verdicts {
+                    match verdict.status {
+                        Status::Pass => passed += 1,
+                        Status::Fail { .. } => failed += 1,
+                        Status::Missing => missing += 1,
+                    }
+                    println!("{verdict}");
+                }
+            }
+            Err(error) => {
+                println!(
+                    "FAIL    puzzle #{}: {}",
+                    day.number,
+                    describe(&error, &source_name)
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    println!("\n{passed} passed, {failed} failed, {missing} missing");
+    failed == 0 && missing == 0
+}
+
+// Returns false when the day 2 input or the bag can't be read
+fn stats(options: &StatsOptions) -> bool {
+    let bag = match options.bag.as_deref().map(Bag::parse).transpose() {
+        Ok(bag) => bag.unwrap_or_else(Bag::elf),
+        Err(error) => {
+            eprintln!("{}", describe(&error, "--bag"));
+            return false;
+        }
+    };
+    let source = match input_source(&options.input) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{error}");
+            return false;
+        }
+    };
+    let games = match source.read(2).and_then(|input| GameSet::parse(&input)) {
+        Ok(games) => games,
+        Err(error) => {
+            eprintln!("{}", describe(&error, &source.name(2)));
+            return false;
+        }
+    };
+
+    let report = if options.json {
+        puzzle2::render_stats_json(&games, &bag).map(|json| format!("{json}\n"))
+    } else {
+        puzzle2::render_stats_text(&games, &bag)
+    };
+    match report {
+        Ok(report) => {
+            print!("{report}");
+            true
+        }
+        Err(error) => {
+            eprintln!("{}", describe(&error, &source.name(2)));
+            false
+        }
+    }
+}
+
+// Colors only go to a terminal, and never when NO_COLOR is set to anything (https://no-color.org)
+fn use_color() -> bool {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    io::stdout().is_terminal() && !no_color
+}
+
+// Returns false when the day 3 input or the gear rule can't be read
+fn schematic(options: &SchematicOptions) -> bool {
+    let rule = match options.gears.as_deref().map(GearRule::parse).transpose() {
+        Ok(rule) => rule.unwrap_or_default(),
+        Err(error) => {
+            eprintln!("{}", describe(&error, "--gears"));
+            return false;
+        }
+    };
+    let source = match input_source(&options.input) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("{error}");
+            return false;
+        }
+    };
+    let schematic = match source
+        .read(3)
+        .and_then(|input| puzzle3::parse_input(&input))
+    {
+        Ok(schematic) => schematic,
+        Err(error) => {
+            eprintln!("{}", describe(&error, &source.name(3)));
+            return false;
+        }
+    };
+
+    print!(
+        "{}",
+        match options.format {
+            SchematicFormat::Auto if use_color() => puzzle3::render_ansi(&schematic, &rule),
+            SchematicFormat::Auto => puzzle3::render_plain(&schematic),
+            SchematicFormat::Ansi => puzzle3::render_ansi(&schematic, &rule),
+            SchematicFormat::Html => puzzle3::render_html(&schematic, &rule),
+            SchematicFormat::Svg => puzzle3::render_svg(&schematic, &rule),
+        }
+    );
+    true
+}
+
+fn main() {
+    let mut registry = solution::registry();
 
-    let (answer6_1, answer6_2) = puzzle6::answer();
-    println!("Answer to puzzle #6 (Part one): {answer6_1}");
-    println!("Answer to puzzle #6 (Part two): {answer6_2}");
+    match cli::parse_args(env::args().skip(1), &registry.numbers()) {
+        Ok(Command::Run(options)) => {
+            let vocabulary = match &options.vocabulary {
+                Some(name_or_path) => match load_vocabulary(name_or_path) {
+                    Ok(vocabulary) => vocabulary,
+                    Err(error) => {
+                        eprintln!("{}", describe(&error, name_or_path));
+                        process::exit(1);
+                    }
+                },
+                None => Vocabulary::default(),
+            };
+            let succeeded = if options.explain {
+                explain(&options, &vocabulary)
+            } else {
+                registry.register(1, Puzzle1 { vocabulary });
+                if let Some(cubes) = &options.bag {
+                    match Bag::parse(cubes) {
+                        Ok(bag) => registry.register(2, Puzzle2 { bag }),
+                        Err(error) => {
+                            eprintln!("{}", describe(&error, "--bag"));
+                            process::exit(1);
+                        }
+                    };
+                }
+                if let Some(rule) = &options.gears {
+                    match GearRule::parse(rule) {
+                        Ok(gears) => registry.register(3, Puzzle3 { gears }),
+                        Err(error) => {
+                            eprintln!("{}", describe(&error, "--gears"));
+                            process::exit(1);
+                        }
+                    };
+                }
+                run(&registry, &options)
+            };
+            if !succeeded {
+                process::exit(1);
+            }
+        }
+        Ok(Command::Verify(options)) => {
+            if !verify(&registry, &options) {
+                process::exit(1);
+            }
+        }
+        Ok(Command::Stats(options)) => {
+            if !stats(&options) {
+                process::exit(1);
+            }
+        }
+        Ok(Command::Schematic(options)) => {
+            if !schematic(&options) {
+                process::exit(1);
+            }
+        }
+        Ok(Command::Help) => println!("{}", cli::USAGE),
+        Err(error) => {
+            eprintln!("{error}\n\n{}", cli::USAGE);
+            process::exit(2);
+        }
+    }
 }
@@ -1,74 +1,757 @@
-use std::{collections::HashMap, fs};
-
-pub fn get_input() -> String {
-    fs::read_to_string("puzzle1.txt").expect("File not found.")
-}
-
-pub fn answer() -> u32 {
-    let number_names: HashMap<&str, char> = HashMap::from([
-        ("one", '1'),
-        ("two", '2'),
-        ("three", '3'),
-        ("four", '4'),
-        ("five", '5'),
-        ("six", '6'),
-        ("seven", '7'),
-        ("eight", '8'),
-        ("nine", '9'),
-        ("zero", '0'),
-    ]);
-
-    let answer: u32 = get_input()
-        .lines()
-        .map(|l| {
-            // find first number
-            let first_digit_position = l.chars().position(|c| c.is_numeric()).unwrap();
-            let first_word_position = number_names
+use std::{collections::VecDeque, fmt::Write, ops::Range, str::CharIndices};
+
+use crate::{
+    config,
+    error::{Error, Location, Result},
+    json,
+    solution::{Part, Solution},
+};
+
+const ENGLISH: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
+
+const SPANISH: [(&str, u32); 9] = [
+    ("uno", 1),
+    ("dos", 2),
+    ("tres", 3),
+    ("cuatro", 4),
+    ("cinco", 5),
+    ("seis", 6),
+    ("siete", 7),
+    ("ocho", 8),
+    ("nueve", 9),
+];
+
+const GERMAN: [(&str, u32); 9] = [
+    ("eins", 1),
+    ("zwei", 2),
+    ("drei", 3),
+    ("vier", 4),
+    ("fünf", 5),
+    ("sechs", 6),
+    ("sieben", 7),
+    ("acht", 8),
+    ("neun", 9),
+];
+
+/// Spelled-out words that count as calibration digits, on top of the numeric characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vocabulary(Vec<(String, u32)>);
+
+impl Vocabulary {
+    /// Names accepted by [`Vocabulary::named`].
+    pub const BUILT_IN: [&'static str; 4] = ["english", "english-with-zero", "spanish", "german"];
+
+    fn from_words(words: &[(&str, u32)]) -> Self {
+        Vocabulary(
+            words
                 .iter()
-                .filter_map(|(word, num)| l.find(word).map(|idx| (idx, num)))
-                .min_by(|a, b| a.0.cmp(&b.0));
-
-            let first_digit = match first_word_position {
-                Some((word_position, char)) => {
-                    if first_digit_position < word_position {
-                        l.chars().nth(first_digit_position).unwrap()
-                    } else {
-                        *char
-                    }
-                }
-                None => l.chars().nth(first_digit_position).unwrap(),
+                .map(|&(word, value)| (word.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// No words at all, only numeric characters count, as in part one.
+    pub fn digits_only() -> Self {
+        Vocabulary(vec![])
+    }
+
+    /// "one" to "nine", as in part two. The puzzle never spells out zero.
+    pub fn english() -> Self {
+        Vocabulary::from_words(&ENGLISH)
+    }
+
+    pub fn english_with_zero() -> Self {
+        let mut vocabulary = Vocabulary::english();
+        vocabulary.0.push((String::from("zero"), 0));
+        vocabulary
+    }
+
+    pub fn spanish() -> Self {
+        Vocabulary::from_words(&SPANISH)
+    }
+
+    pub fn german() -> Self {
+        Vocabulary::from_words(&GERMAN)
+    }
+
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "english" => Some(Vocabulary::english()),
+            "english-with-zero" => Some(Vocabulary::english_with_zero()),
+            "spanish" => Some(Vocabulary::spanish()),
+            "german" => Some(Vocabulary::german()),
+            _ => None,
+        }
+    }
+
+    /// Reads a custom word list with one `word = digit` entry per line. Word lists have no
+    /// sections, so a `[section]` header is an error.
+    pub fn parse(input: &str) -> Result<Self> {
+        if let Some((idx, line)) = input
+            .lines()
+            .enumerate()
+            .find(|(_, line)| line.trim_start().starts_with('['))
+        {
+            return Err(Error::malformed(
+                "a `word = digit` entry",
+                line.trim(),
+                Location::default(),
+            )
+            .at_line(idx + 1));
+        }
+        config::parse_key_values(input)?
+            .into_iter()
+            .map(|(word, value)| match value.parse::<u32>() {
+                Ok(digit) if digit <= 9 => Ok((word, digit)),
+                _ => Err(Error::bad_number(&value, Location::default())),
+            })
+            .collect::<Result<Vec<(String, u32)>>>()
+            .map(Vocabulary)
+    }
+
+    pub fn words(&self) -> impl Iterator<Item = (&str, u32)> {
+        self.0.iter().map(|(word, value)| (word.as_str(), *value))
+    }
+}
+
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Vocabulary::english()
+    }
+}
+
+/// A digit found in a line, either numeric or spelled out, with its byte span in the line.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DigitToken<'a> {
+    pub span: Range<usize>,
+    pub text: &'a str,
+    pub value: u32,
+}
+
+/// Iterator over the digits of a line, see [`digit_tokens`].
+#[derive(Debug, Clone)]
+pub struct DigitTokens<'l, 'v> {
+    line: &'l str,
+    vocabulary: &'v Vocabulary,
+    chars: CharIndices<'l>,
+}
+
+impl<'l> Iterator for DigitTokens<'l, '_> {
+    type Item = DigitToken<'l>;
+
+    fn next(&mut self) -> Option<DigitToken<'l>> {
+        let line = self.line;
+        for (idx, c) in self.chars.by_ref() {
+            let found = match c.to_digit(10) {
+                Some(value) => Some((idx..idx + c.len_utf8(), value)),
+                None => self
+                    .vocabulary
+                    .words()
+                    .filter(|(word, _)| line[idx..].starts_with(word))
+                    .max_by_key(|(word, _)| word.len())
+                    .map(|(word, value)| (idx..idx + word.len(), value)),
             };
+            if let Some((span, value)) = found {
+                return Some(DigitToken {
+                    text: &line[span.clone()],
+                    span,
+                    value,
+                });
+            }
+        }
+        None
+    }
+}
 
-            // find last number
-            let last_digit_position = l.chars().rev().position(|c| c.is_numeric()).unwrap();
-            let last_word_position = number_names
-                .iter()
-                .filter_map(|(word, num)| l.rfind(word).map(|idx| (idx, num)))
-                .max_by(|a, b| a.0.cmp(&b.0));
-
-            let last_digit = match last_word_position {
-                Some((word_position, char)) => {
-                    if (l.len() - last_digit_position - 1) > word_position {
-                        l.chars().nth_back(last_digit_position).unwrap()
-                    } else {
-                        *char
+/// Every digit occurrence of a line, in order of their start. Spelled-out digits may overlap, so
+/// "twone" yields both 2 and 1. When several words start at the same byte the longest one wins.
+pub fn digit_tokens<'l, 'v>(line: &'l str, vocabulary: &'v Vocabulary) -> DigitTokens<'l, 'v> {
+    DigitTokens {
+        line,
+        vocabulary,
+        chars: line.char_indices(),
+    }
+}
+
+/// The two digit calibration value of a line: its first digit followed by its last digit.
+pub fn calibration_value(line: &str, vocabulary: &Vocabulary) -> Result<u32> {
+    let mut tokens = digit_tokens(line, vocabulary);
+    let Some(first) = tokens.next() else {
+        return Err(Error::missing("a digit", Location::default()));
+    };
+    let last = tokens.last().unwrap_or_else(|| first.clone());
+    Ok(first.value * 10 + last.value)
+}
+
+const NUMERIC_DIGITS: [(&str, u32); 10] = [
+    ("0", 0),
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+];
+
+// Aho-Corasick automaton over bytes with the failure links already folded into the transition
+// table, so every input byte costs a single lookup.
+#[derive(Debug)]
+struct Automaton {
+    transitions: Vec<[u32; 256]>,
+    // Longest pattern ending in each state, including the ones reached through failure links.
+    matches: Vec<Option<usize>>,
+}
+
+impl Automaton {
+    fn new(patterns: &[Vec<u8>]) -> Self {
+        let mut transitions = vec![[0u32; 256]];
+        let mut matches = vec![None];
+
+        // State 0 is the root and never a trie child, so 0 marks a missing edge while building.
+        for (idx, pattern) in patterns.iter().enumerate() {
+            let mut state = 0;
+            for &byte in pattern {
+                let next = transitions[state][byte as usize] as usize;
+                state = if next == 0 {
+                    transitions.push([0; 256]);
+                    matches.push(None);
+                    let next = transitions.len() - 1;
+                    // Each state takes 1 KiB, so memory runs out long before the ids do
+                    transitions[state][byte as usize] =
+                        u32::try_from(next).expect("Too many automaton states");
+                    next
+                } else {
+                    next
+                };
+            }
+            if matches[state].is_none() {
+                matches[state] = Some(idx);
+            }
+        }
+
+        let mut fail = vec![0; transitions.len()];
+        let mut queue = VecDeque::from_iter(transitions[0].iter().filter(|&&s| s != 0).copied());
+        while let Some(state) = queue.pop_front() {
+            let state = state as usize;
+            // A failure link points to a proper suffix, so its match is always shorter.
+            if matches[state].is_none() {
+                matches[state] = matches[fail[state]];
+            }
+            let fallback = transitions[fail[state]];
+            for (next, fallback) in transitions[state].iter_mut().zip(fallback) {
+                if *next == 0 {
+                    *next = fallback;
+                } else {
+                    fail[*next as usize] = fallback as usize;
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        Automaton {
+            transitions,
+            matches,
+        }
+    }
+
+    fn step(&self, state: usize, byte: u8) -> (usize, Option<usize>) {
+        let next = self.transitions[state][byte as usize] as usize;
+        (next, self.matches[next])
+    }
+}
+
+/// Finds the first and last digit of a line in a single forward and a single backward pass,
+/// whatever the number of digit words. Build it once and reuse it for every line.
+#[derive(Debug)]
+pub struct DigitMatcher {
+    values: Vec<u32>,
+    lengths: Vec<usize>,
+    longest: usize,
+    forward: Automaton,
+    backward: Automaton,
+}
+
+impl DigitMatcher {
+    pub fn new(vocabulary: &Vocabulary) -> Self {
+        let words = NUMERIC_DIGITS
+            .into_iter()
+            .chain(vocabulary.words())
+            .collect::<Vec<(&str, u32)>>();
+        let patterns = words
+            .iter()
+            .map(|(pattern, _)| pattern.as_bytes().to_vec())
+            .collect::<Vec<Vec<u8>>>();
+        let reversed = patterns
+            .iter()
+            .map(|pattern| pattern.iter().rev().copied().collect())
+            .collect::<Vec<Vec<u8>>>();
+
+        DigitMatcher {
+            values: words.iter().map(|&(_, value)| value).collect(),
+            lengths: patterns.iter().map(Vec::len).collect(),
+            longest: patterns.iter().map(Vec::len).max().unwrap_or(0),
+            forward: Automaton::new(&patterns),
+            backward: Automaton::new(&reversed),
+        }
+    }
+
+    fn token<'a>(&self, line: &'a str, start: usize, pattern: usize) -> DigitToken<'a> {
+        let span = start..start + self.lengths[pattern];
+        DigitToken {
+            text: &line[span.clone()],
+            span,
+            value: self.values[pattern],
+        }
+    }
+
+    /// The digit that starts first in the line.
+    pub fn first<'a>(&self, line: &'a str) -> Option<DigitToken<'a>> {
+        let mut state = 0;
+        let mut best: Option<(usize, usize)> = None;
+        for (idx, &byte) in line.as_bytes().iter().enumerate() {
+            // Matches are reported where they end, so a longer pattern that starts earlier, or at
+            // the same byte, can still complete a few bytes after the first match.
+            if best.is_some_and(|(start, _)| idx >= start + self.longest) {
+                break;
+            }
+            let found;
+            (state, found) = self.forward.step(state, byte);
+            if let Some(pattern) = found {
+                let start = idx + 1 - self.lengths[pattern];
+                let better = match best {
+                    None => true,
+                    Some((best_start, best_pattern)) => {
+                        start < best_start
+                            || (start == best_start
+                                && self.lengths[pattern] > self.lengths[best_pattern])
                     }
+                };
+                if better {
+                    best = Some((start, pattern));
                 }
-                None => l.chars().nth_back(last_digit_position).unwrap(),
-            };
+            }
+        }
+        best.map(|(start, pattern)| self.token(line, start, pattern))
+    }
+
+    /// The digit that starts last in the line.
+    pub fn last<'a>(&self, line: &'a str) -> Option<DigitToken<'a>> {
+        // Scanning backwards, a match is reported at its start, so the first one found wins.
+        let mut state = 0;
+        for (idx, &byte) in line.as_bytes().iter().enumerate().rev() {
+            let found;
+            (state, found) = self.backward.step(state, byte);
+            if let Some(pattern) = found {
+                return Some(self.token(line, idx, pattern));
+            }
+        }
+        None
+    }
+
+    pub fn calibration_value(&self, line: &str) -> Result<u32> {
+        match (self.first(line), self.last(line)) {
+            (Some(first), Some(last)) => Ok(first.value * 10 + last.value),
+            _ => Err(Error::missing("a digit", Location::default())),
+        }
+    }
+}
+
+pub fn sum_calibration_values<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+    vocabulary: &Vocabulary,
+) -> Result<u32> {
+    let matcher = DigitMatcher::new(vocabulary);
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            matcher
+                .calibration_value(line)
+                .map_err(|e| e.at_line(idx + 1))
+        })
+        .sum()
+}
 
-            // DEBUG info
-            // println!("-----------");
-            // println!("Line: {}", l);
-            // println!("Digit: {}{}", first_digit, last_digit);
-            // println!("First digit position: {}", first_digit_position);
-            // println!("First word position: {:?}", first_word_position);
-            // println!("Last digit position: {}", last_digit_position);
-            // println!("Last word position: {:?}", last_word_position);
+/// How the calibration value of one line was found, as shown by `aoc run --explain`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LineTrace<'a> {
+    /// 1-based line number.
+    pub line: usize,
+    pub text: &'a str,
+    pub first: Option<DigitToken<'a>>,
+    pub last: Option<DigitToken<'a>>,
+}
 
-            format!("{}{}", first_digit, last_digit)
+impl LineTrace<'_> {
+    /// `None` for a line without any digit.
+    pub fn value(&self) -> Option<u32> {
+        Some(self.first.as_ref()?.value * 10 + self.last.as_ref()?.value)
+    }
+}
+
+pub fn trace<'a>(input: &'a str, vocabulary: &Vocabulary) -> Vec<LineTrace<'a>> {
+    let matcher = DigitMatcher::new(vocabulary);
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, text)| LineTrace {
+            line: idx + 1,
+            text,
+            first: matcher.first(text),
+            last: matcher.last(text),
         })
-        .map(|n| n.parse::<u32>().unwrap())
-        .sum();
-    answer
+        .collect()
+}
+
+fn trace_cells<'a>(token: &Option<DigitToken<'a>>) -> (String, &'a str, String) {
+    match token {
+        Some(token) => (
+            token.value.to_string(),
+            token.text,
+            (token.span.start + 1).to_string(),
+        ),
+        None => (String::from("-"), "-", String::from("-")),
+    }
+}
+
+/// One table per part. Columns are 1-based byte offsets, like the ones in parse errors.
+pub fn render_trace_table(traces: &[(Part, Vec<LineTrace>)]) -> String {
+    let mut table = String::new();
+    for (part, lines) in traces {
+        if !table.is_empty() {
+            table.push('\n');
+        }
+        writeln!(
+            table,
+            "{part}\n{:>5}  {:>5}  {:<6}  {:>6}  {:>5}  {:<6}  {:>6}  {:>5}  Text",
+            "Line", "First", "Token", "Column", "Last", "Token", "Column", "Value"
+        )
+        .unwrap();
+        for trace in lines {
+            let (first, first_token, first_column) = trace_cells(&trace.first);
+            let (last, last_token, last_column) = trace_cells(&trace.last);
+            let value = trace.value().map_or(String::from("-"), |v| v.to_string());
+            writeln!(
+                table,
+                "{:>5}  {first:>5}  {first_token:<6}  {first_column:>6}  \
+                {last:>5}  {last_token:<6}  {last_column:>6}  {value:>5}  {}",
+                trace.line, trace.text
+            )
+            .unwrap();
+        }
+        let total = lines.iter().filter_map(LineTrace::value).sum::<u32>();
+        writeln!(table, "Total: {total}").unwrap();
+    }
+    table
+}
+
+fn json_token(token: &Option<DigitToken>) -> String {
+    match token {
+        Some(token) => format!(
+            "{{\"value\":{},\"token\":{},\"start\":{},\"end\":{}}}",
+            token.value,
+            json::string(token.text),
+            token.span.start,
+            token.span.end
+        ),
+        None => String::from("null"),
+    }
+}
+
+/// Spans are byte offsets into the line, with an exclusive end.
+pub fn render_trace_json(traces: &[(Part, Vec<LineTrace>)]) -> String {
+    let parts = traces
+        .iter()
+        .map(|(part, lines)| {
+            let number = match part {
+                Part::One => 1,
+                Part::Two => 2,
+            };
+            let lines = lines
+                .iter()
+                .map(|trace| {
+                    format!(
+                        "{{\"line\":{},\"text\":{},\"first\":{},\"last\":{},\"value\":{}}}",
+                        trace.line,
+                        json::string(trace.text),
+                        json_token(&trace.first),
+                        json_token(&trace.last),
+                        trace
+                            .value()
+                            .map_or(String::from("null"), |v| v.to_string())
+                    )
+                })
+                .collect::<Vec<String>>()
+                .join(",");
+            format!("{{\"part\":{number},\"lines\":[{lines}]}}")
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+    format!("[{parts}]")
+}
+
+pub fn answer_part_1(input: &str) -> Result<u32> {
+    sum_calibration_values(input.lines(), &Vocabulary::digits_only())
+}
+
+pub fn answer_part_2(input: &str) -> Result<u32> {
+    sum_calibration_values(input.lines(), &Vocabulary::english())
+}
+
+/// Part two counts the words of `vocabulary`, English by default.
+#[derive(Debug, Default)]
+pub struct Puzzle1 {
+    pub vocabulary: Vocabulary,
+}
+
+// Which characters count as digits depends on the part, so the lines are all there is to parse
+impl Solution for Puzzle1 {
+    type Input = Vec<String>;
+    type Answer = u32;
+
+    fn parse(&self, input: &str) -> Result<Self::Input> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Result<Option<Self::Answer>> {
+        sum_calibration_values(input.iter().map(String::as_str), &Vocabulary::digits_only())
+            .map(Some)
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Result<Option<Self::Answer>> {
+        sum_calibration_values(input.iter().map(String::as_str), &self.vocabulary).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        puzzle1::{
+            answer_part_1, answer_part_2, calibration_value, digit_tokens, render_trace_json,
+            render_trace_table, trace, DigitMatcher, DigitToken, Vocabulary,
+        },
+        solution::Part,
+    };
+
+    #[test]
+    fn test_calibration_value() {
+        assert_eq!(
+            calibration_value("pqr3stu8vwx", &Vocabulary::digits_only()).unwrap(),
+            38
+        );
+        assert_eq!(
+            calibration_value("treb7uchet", &Vocabulary::digits_only()).unwrap(),
+            77
+        );
+        assert_eq!(
+            calibration_value("two1nine", &Vocabulary::digits_only()).unwrap(),
+            11
+        );
+        assert_eq!(
+            calibration_value("two1nine", &Vocabulary::english()).unwrap(),
+            29
+        );
+        assert!(calibration_value("abc", &Vocabulary::digits_only()).is_err());
+    }
+
+    #[test]
+    fn test_digit_tokens_overlap() {
+        let tokens = digit_tokens("xtwone3", &Vocabulary::english()).collect::<Vec<_>>();
+
+        assert_eq!(
+            tokens,
+            vec![
+                DigitToken {
+                    span: 1..4,
+                    text: "two",
+                    value: 2
+                },
+                DigitToken {
+                    span: 3..6,
+                    text: "one",
+                    value: 1
+                },
+                DigitToken {
+                    span: 6..7,
+                    text: "3",
+                    value: 3
+                },
+            ]
+        );
+        assert_eq!(
+            digit_tokens("xtwone3", &Vocabulary::digits_only()).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_calibration_value_without_numeric_digits() {
+        assert_eq!(
+            calibration_value("eightwo", &Vocabulary::english()).unwrap(),
+            82
+        );
+        assert_eq!(
+            calibration_value("nine", &Vocabulary::english()).unwrap(),
+            99
+        );
+        assert_eq!(
+            answer_part_1("12\neightwo").unwrap_err().to_string(),
+            "2: missing a digit"
+        );
+    }
+
+    #[test]
+    fn test_calibration_value_non_ascii() {
+        let tokens = digit_tokens("é1ñtwo", &Vocabulary::english()).collect::<Vec<_>>();
+
+        assert_eq!(tokens[0].span, 2..3);
+        assert_eq!(tokens[1].span, 5..8);
+        assert_eq!(
+            calibration_value("ñ٣7ünine", &Vocabulary::english()).unwrap(),
+            79
+        );
+    }
+
+    #[test]
+    fn test_matcher_agrees_with_tokens() {
+        let lines = [
+            "two1nine",
+            "eightwo3three",
+            "xtwone3four",
+            "zoneight234",
+            "oneight",
+            "7pqrstsixteen",
+            "é1ñtwoñ",
+            "sevenine",
+            "abc",
+        ];
+        for vocabulary in [Vocabulary::digits_only(), Vocabulary::english()] {
+            let matcher = DigitMatcher::new(&vocabulary);
+            for line in lines {
+                let tokens = digit_tokens(line, &vocabulary).collect::<Vec<_>>();
+
+                assert_eq!(matcher.first(line).as_ref(), tokens.first(), "{line}");
+                assert_eq!(matcher.last(line).as_ref(), tokens.last(), "{line}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_built_in_vocabularies() {
+        assert_eq!(
+            calibration_value("zero1", &Vocabulary::english()).unwrap(),
+            11
+        );
+        assert_eq!(
+            calibration_value("zero1", &Vocabulary::english_with_zero()).unwrap(),
+            1
+        );
+        assert_eq!(
+            calibration_value("xdosietex", &Vocabulary::spanish()).unwrap(),
+            27
+        );
+        assert_eq!(
+            calibration_value("fünfzehneins", &Vocabulary::german()).unwrap(),
+            51
+        );
+        assert_eq!(Vocabulary::named("german"), Some(Vocabulary::german()));
+        assert_eq!(Vocabulary::named("klingon"), None);
+    }
+
+    #[test]
+    fn test_custom_vocabulary() {
+        let vocabulary =
+            Vocabulary::parse("# Nested words\nsept = 7\nseptante = 0\nant = 1").unwrap();
+        let matcher = DigitMatcher::new(&vocabulary);
+
+        assert_eq!(calibration_value("septante", &vocabulary).unwrap(), 1);
+        assert_eq!(matcher.calibration_value("septante").unwrap(), 1);
+        assert_eq!(matcher.calibration_value("2septant").unwrap(), 21);
+
+        assert!(Vocabulary::parse("ten = 10").is_err());
+        assert!(Vocabulary::parse("ten = x").is_err());
+        assert_eq!(
+            Vocabulary::parse("sept = 7\n[words]\none = 1")
+                .unwrap_err()
+                .to_string(),
+            "2: expected a `word = digit` entry, found '[words]'"
+        );
+    }
+
+    #[test]
+    fn test_large_vocabulary() {
+        // Four letter words give more automaton states than fit in 16 bits
+        let words = (0..64_000_u32)
+            .map(|idx| {
+                let word = (0..4)
+                    .rev()
+                    .map(|place| char::from(b'a' + (idx / 26_u32.pow(place) % 26) as u8))
+                    .collect::<String>();
+                format!("{word} = {}\n", idx % 10)
+            })
+            .collect::<String>();
+        let matcher = DigitMatcher::new(&Vocabulary::parse(&words).unwrap());
+
+        assert_eq!(matcher.calibration_value("aaab1x").unwrap(), 11);
+        // "bbbb" is word 18279
+        assert_eq!(matcher.calibration_value("zz7bbbb").unwrap(), 79);
+    }
+
+    #[test]
+    fn test_trace() {
+        let traces = trace("two1nine\nabc\n", &Vocabulary::english());
+
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[0].first.as_ref().map(|t| t.text), Some("two"));
+        assert_eq!(traces[0].last.as_ref().map(|t| t.span.clone()), Some(4..8));
+        assert_eq!(traces[0].value(), Some(29));
+        assert_eq!(traces[1].value(), None);
+
+        let table = render_trace_table(&[(Part::Two, traces)]);
+        assert_eq!(
+            table.lines().nth(2),
+            Some("    1      2  two          1      9  nine         5     29  two1nine")
+        );
+        assert_eq!(table.lines().last(), Some("Total: 29"));
+    }
+
+    #[test]
+    fn test_render_trace_json() {
+        let traces = trace("a1", &Vocabulary::digits_only());
+
+        assert_eq!(
+            render_trace_json(&[(Part::One, traces)]),
+            "[{\"part\":1,\"lines\":[{\"line\":1,\"text\":\"a1\",\
+            \"first\":{\"value\":1,\"token\":\"1\",\"start\":1,\"end\":2},\
+            \"last\":{\"value\":1,\"token\":\"1\",\"start\":1,\"end\":2},\"value\":11}]}]"
+        );
+    }
+
+    #[test]
+    fn test_puzzle_answer_part_1() {
+        let puzzle_input = "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet";
+
+        assert_eq!(answer_part_1(puzzle_input).unwrap(), 142);
+    }
+
+    #[test]
+    fn test_puzzle_answer_part_2() {
+        let puzzle_input = "two1nine\neightwo3three\nabcone2threexyz\nxtwone3four\n\
+            4nineeightseven2\nzoneight234\n7pqrstsixteen";
+
+        assert_eq!(answer_part_2(puzzle_input).unwrap(), 281);
+    }
 }
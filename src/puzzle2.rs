@@ -1,98 +1,227 @@
-use std::{collections::HashMap, fs};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{self, Write},
+    ops::Range,
+    sync::OnceLock,
+};
 
 use regex::Regex;
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash)]
-pub enum CubeColor {
-    Red,
-    Green,
-    Blue,
+use crate::{
+    error::{column_of, Error, Location, Result},
+    json,
+    solution::Solution,
+};
+
+/// A cube color. Any name is accepted, and the same name always gives the same color.
+#[derive(PartialEq, Eq, Hash, Clone)]
+pub struct CubeColor(Cow<'static, str>);
+
+impl CubeColor {
+    pub const RED: CubeColor = CubeColor(Cow::Borrowed("red"));
+    pub const GREEN: CubeColor = CubeColor(Cow::Borrowed("green"));
+    pub const BLUE: CubeColor = CubeColor(Cow::Borrowed("blue"));
+
+    /// The colors of the original puzzle, which every bag and game knows about.
+    pub const STANDARD: [CubeColor; 3] = [CubeColor::RED, CubeColor::GREEN, CubeColor::BLUE];
+
+    pub fn named(name: &str) -> Self {
+        // The standard colors are shared instead of allocating a name for every cube drawn
+        CubeColor::STANDARD
+            .into_iter()
+            .find(|color| color.name() == name)
+            .unwrap_or_else(|| CubeColor(Cow::Owned(name.to_string())))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    fn sort_key(&self) -> (usize, &str) {
+        let standard = CubeColor::STANDARD.iter().position(|color| color == self);
+        (standard.unwrap_or(CubeColor::STANDARD.len()), self.name())
+    }
 }
 
-#[derive(Debug)]
-pub struct CubeColorParsingError;
+// The standard colors come first, in puzzle order, then any other color by name. This gives a
+// stable order for output.
+impl Ord for CubeColor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl PartialOrd for CubeColor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Debug for CubeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CubeColor({})", self.name())
+    }
+}
+
+impl fmt::Display for CubeColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
 
 impl TryFrom<&str> for CubeColor {
-    type Error = CubeColorParsingError;
-
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value.trim() {
-            "red" => Ok(CubeColor::Red),
-            "green" => Ok(CubeColor::Green),
-            "blue" => Ok(CubeColor::Blue),
-            _ => Err(CubeColorParsingError),
+    type Error = Error;
+
+    // Color names are made of letters only
+    fn try_from(value: &str) -> Result<Self> {
+        let name = value.trim();
+        if name.is_empty() || !name.chars().all(char::is_alphabetic) {
+            return Err(Error::UnknownColor {
+                text: name.to_string(),
+                location: Location::default(),
+            });
         }
+        Ok(CubeColor::named(name))
     }
 }
 
-#[derive(Debug)]
+fn game_regex() -> &'static Regex {
+    static GAME_REGEX: OnceLock<Regex> = OnceLock::new();
+    GAME_REGEX.get_or_init(|| Regex::new(r"^Game (\S+)$").expect("Wrong regex pattern"))
+}
+
+// The whole entry has to be an amount and a color, so "3 blue 4" or "x3 blue" are rejected.
+// Compiled on first use and shared by every line.
+fn cube_regex() -> &'static Regex {
+    static CUBE_REGEX: OnceLock<Regex> = OnceLock::new();
+    CUBE_REGEX.get_or_init(|| Regex::new(r"^\s*(\S+)\s+(\S+)\s*$").expect("Wrong regex pattern"))
+}
+
+// Parses "# color, # color" where `record` is a part of `line`, so errors point at the column of
+// the offending text in the line.
+fn parse_cube_set(line: &str, record: &str) -> Result<HashMap<CubeColor, i32>> {
+    let location = |part: &str| Location {
+        line: None,
+        column: column_of(line, part),
+    };
+    let mut map_set = HashMap::<CubeColor, i32>::new();
+
+    for set in record.split(',') {
+        // An empty entry means a trailing or doubled comma, or an empty set of cubes
+        if set.trim().is_empty() {
+            return Err(Error::missing("`<amount> <color>`", location(set)));
+        }
+        let Some(captures) = cube_regex().captures(set) else {
+            return Err(Error::malformed(
+                "`<amount> <color>`",
+                set.trim(),
+                location(set.trim()),
+            ));
+        };
+        let (amount, color) = (captures.get(1).unwrap(), captures.get(2).unwrap());
+
+        let cube_color = CubeColor::try_from(color.as_str()).map_err(|_| Error::UnknownColor {
+            text: color.as_str().to_string(),
+            location: location(color.as_str()),
+        })?;
+        let cube_amount = amount
+            .as_str()
+            .parse::<i32>()
+            .ok()
+            .filter(|amount| *amount >= 0)
+            .ok_or_else(|| Error::bad_number(amount.as_str(), location(amount.as_str())))?;
+
+        if map_set.insert(cube_color, cube_amount).is_some() {
+            return Err(Error::DuplicateColor {
+                text: color.as_str().to_string(),
+                location: location(color.as_str()),
+            });
+        }
+    }
+    Ok(map_set)
+}
+
+/// How many cubes of every color the bag holds. Colors without a limit are not in the bag at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bag(HashMap<CubeColor, i32>);
+
+impl Bag {
+    /// The bag from the puzzle: 12 red, 13 green and 14 blue cubes.
+    pub fn elf() -> Self {
+        Bag::default()
+            .with(CubeColor::RED, 12)
+            .with(CubeColor::GREEN, 13)
+            .with(CubeColor::BLUE, 14)
+    }
+
+    pub fn with(mut self, color: CubeColor, amount: i32) -> Self {
+        self.0.insert(color, amount);
+        self
+    }
+
+    pub fn limit(&self, color: &CubeColor) -> i32 {
+        self.0.get(color).copied().unwrap_or(0)
+    }
+
+    /// Number of cubes of all colors together.
+    pub fn total(&self) -> i64 {
+        self.0.values().map(|&amount| i64::from(amount)).sum()
+    }
+
+    /// Reads a bag written like a set of cubes in a game, e.g. "12 red, 13 green, 14 blue".
+    pub fn parse(cubes: &str) -> Result<Self> {
+        parse_cube_set(cubes, cubes).map(Bag)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
 pub struct Game {
     pub id: i32,
     pub cubes: Vec<HashMap<CubeColor, i32>>,
 }
 
-#[derive(Debug)]
-pub struct GameParseInputError;
-
 impl TryFrom<&str> for Game {
-    type Error = GameParseInputError;
-
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let game_regex = Regex::new(r"^Game (\d+)$").unwrap();
-        let cube_regex = Regex::new(r"(\d+)\s(\w+)").unwrap();
-        let (game_str, cube_set_str) = value.split_once(':').unzip();
+    type Error = Error;
+
+    // Expecting string in the format "Game #: # color, # color; # color". Every failure is
+    // reported with the column of the offending text.
+    fn try_from(value: &str) -> Result<Self> {
+        let location = |part: &str| Location {
+            line: None,
+            column: column_of(value, part),
+        };
 
-        let Some(game_id) = game_str
-            .and_then(|s| game_regex.captures(s))
-            .and_then(|capture| capture.get(1).map(|r| r.as_str()))
-            .map(|id| id.parse::<i32>().expect("Error parsing game id"))
-        else {
-            return Err(GameParseInputError);
+        let Some((game_str, cube_set_str)) = value.split_once(':') else {
+            return Err(Error::malformed(
+                "`Game <id>: <cubes>`",
+                value,
+                Location::default(),
+            ));
         };
 
-        let Some(cubes_str) = cube_set_str.map(|str| {
-            str.split(';')
-                .map(|s| s.split(',').collect::<Vec<&str>>())
-                .collect::<Vec<Vec<&str>>>()
-        }) else {
-            return Err(GameParseInputError);
+        let Some(id_str) = game_regex()
+            .captures(game_str)
+            .and_then(|capture| capture.get(1))
+            .map(|id| id.as_str())
+        else {
+            return Err(Error::malformed(
+                "`Game <id>`",
+                game_str,
+                location(game_str),
+            ));
         };
+        let game_id = id_str
+            .parse::<i32>()
+            .ok()
+            .filter(|id| *id >= 0)
+            .ok_or_else(|| Error::bad_number(id_str, location(id_str)))?;
 
-        let cube_amounts = cubes_str
-            .iter()
-            .map(|record| {
-                let parsed_sets = record
-                    .iter()
-                    .map(|set| {
-                        let match_results = cube_regex.captures(set);
-                        let result = match_results
-                            .map(|captures| captures.get(2).zip(captures.get(1)))
-                            .and_then(|pair| {
-                                pair.map(|(color, amount)| (color.as_str(), amount.as_str()))
-                            })
-                            .map(|(color, amount)| {
-                                (
-                                    CubeColor::try_from(color)
-                                        .map_err(|_| GameParseInputError)
-                                        .expect("Error parsing cube color"),
-                                    amount
-                                        .parse::<i32>()
-                                        .map_err(|_| GameParseInputError)
-                                        .expect("Error parsing cube amount"),
-                                )
-                            });
-                        result.expect("Error parsing game")
-                    })
-                    .collect::<Vec<(CubeColor, i32)>>();
-
-                let mut map_set = HashMap::<CubeColor, i32>::new();
-
-                parsed_sets.iter().for_each(|set| {
-                    map_set.insert(set.0, set.1);
-                });
-                map_set
-            })
-            .collect::<Vec<HashMap<CubeColor, i32>>>();
+        let cube_amounts = cube_set_str
+            .split(';')
+            .map(|record| parse_cube_set(value, record))
+            .collect::<Result<Vec<HashMap<CubeColor, i32>>>>()?;
 
         Ok(Game {
             id: game_id,
@@ -101,76 +230,375 @@ impl TryFrom<&str> for Game {
     }
 }
 
+/// Writes the game back in the input format, with the colors of every set in a stable order.
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}: ", self.id)?;
+        for (idx, set) in self.cubes.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", Cubes(set))?;
+        }
+        Ok(())
+    }
+}
+
+fn sorted_cubes(set: &HashMap<CubeColor, i32>) -> Vec<(&CubeColor, i32)> {
+    let mut cubes = set
+        .iter()
+        .map(|(color, &amount)| (color, amount))
+        .collect::<Vec<(&CubeColor, i32)>>();
+    cubes.sort();
+    cubes
+}
+
+// A set of cubes written as "4 red, 3 blue", in the stable color order.
+struct Cubes<'a>(&'a HashMap<CubeColor, i32>);
+
+impl fmt::Display for Cubes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, (color, amount)) in sorted_cubes(self.0).into_iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{amount} {color}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Written like a set of cubes, so it can be read back with [`Bag::parse`].
+impl fmt::Display for Bag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", Cubes(&self.0))
+    }
+}
+
 impl Game {
-    pub fn power(&self) -> i32 {
-        let minimum_red = self
-            .cubes
+    /// Whether every set of cubes revealed in the game could have been drawn from `bag`.
+    pub fn is_possible_with(&self, bag: &Bag) -> bool {
+        self.impossible_draw(bag).is_none()
+    }
+
+    /// Index of the first set of cubes that could not have been drawn from `bag`.
+    pub fn impossible_draw(&self, bag: &Bag) -> Option<usize> {
+        self.cubes
             .iter()
-            .max_by(|a, b| {
-                a.get(&CubeColor::Red)
-                    .unwrap_or(&0)
-                    .cmp(b.get(&CubeColor::Red).unwrap_or(&0))
-            })
-            .and_then(|cube| cube.get(&CubeColor::Red))
-            .unwrap_or(&1);
+            .position(|set| set.iter().any(|(color, &amount)| amount > bag.limit(color)))
+    }
 
-        let minimum_green = self
-            .cubes
+    /// The fewest cubes of every color that make the game possible, 0 for the standard colors
+    /// when they are never drawn.
+    pub fn minimum_bag(&self) -> Bag {
+        let mut minimum = CubeColor::STANDARD
             .iter()
-            .max_by(|a, b| {
-                a.get(&CubeColor::Green)
-                    .unwrap_or(&0)
-                    .cmp(b.get(&CubeColor::Green).unwrap_or(&0))
+            .map(|color| (color.clone(), 0))
+            .collect::<HashMap<CubeColor, i32>>();
+        for set in &self.cubes {
+            for (color, &amount) in set {
+                let limit = minimum.entry(color.clone()).or_insert(0);
+                *limit = (*limit).max(amount);
+            }
+        }
+        Bag(minimum)
+    }
+
+    /// Product of the minimum amount of every color, so 0 when a standard color is never drawn.
+    /// Fails when the product doesn't fit in 64 bits.
+    pub fn power(&self) -> Result<i64> {
+        self.minimum_bag()
+            .0
+            .values()
+            .try_fold(1_i64, |power, &amount| power.checked_mul(i64::from(amount)))
+            .ok_or(Error::NoSolution(
+                "the power of a game doesn't fit in 64 bits",
+            ))
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Game>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, l)| Game::try_from(l).map_err(|e| e.at_line(idx + 1)))
+        .collect::<Result<Vec<Game>>>()
+}
+
+/// Sum of the ids of the games that are possible with `bag`.
+pub fn sum_possible_ids(games: &[Game], bag: &Bag) -> i64 {
+    games
+        .iter()
+        .filter(|game| game.is_possible_with(bag))
+        .map(|game| i64::from(game.id))
+        .sum()
+}
+
+pub fn answer_part_1(games: &[Game]) -> Result<i64> {
+    Ok(sum_possible_ids(games, &Bag::elf()))
+}
+
+pub fn answer_part_2(games: &[Game]) -> Result<i64> {
+    games.iter().try_fold(0_i64, |sum, game| {
+        sum.checked_add(game.power()?)
+            .ok_or(Error::NoSolution("the answer doesn't fit in 64 bits"))
+    })
+}
+
+/// Draws of a single color across a set of games.
+#[derive(Debug, PartialEq)]
+pub struct ColorStats {
+    pub color: CubeColor,
+    /// Number of sets of cubes that include the color.
+    pub draws: usize,
+    pub max: i32,
+    /// Mean amount over the sets that include the color.
+    pub mean: f64,
+}
+
+/// A list of games with queries that go beyond the two puzzle answers, for `aoc stats`.
+#[derive(Debug)]
+pub struct GameSet(pub Vec<Game>);
+
+impl GameSet {
+    pub fn parse(input: &str) -> Result<Self> {
+        parse_input(input).map(GameSet)
+    }
+
+    /// One entry per color drawn in any game, in the stable color order.
+    pub fn color_stats(&self) -> Vec<ColorStats> {
+        let mut amounts = HashMap::<&CubeColor, Vec<i32>>::new();
+        for set in self.0.iter().flat_map(|game| &game.cubes) {
+            for (color, &amount) in set {
+                amounts.entry(color).or_default().push(amount);
+            }
+        }
+        let mut stats = amounts
+            .into_iter()
+            .map(|(color, amounts)| ColorStats {
+                color: color.clone(),
+                draws: amounts.len(),
+                max: amounts.iter().copied().max().unwrap_or(0),
+                mean: amounts.iter().map(|&a| f64::from(a)).sum::<f64>() / amounts.len() as f64,
             })
-            .and_then(|cube| cube.get(&CubeColor::Green))
-            .unwrap_or(&1);
+            .collect::<Vec<ColorStats>>();
+        stats.sort_by(|a, b| a.color.cmp(&b.color));
+        stats
+    }
 
-        let minimum_blue = self
-            .cubes
+    /// The game whose minimum bag holds the most cubes, the first one on ties.
+    pub fn largest_bag(&self) -> Option<(&Game, Bag)> {
+        self.0
             .iter()
-            .max_by(|a, b| {
-                a.get(&CubeColor::Blue)
-                    .unwrap_or(&0)
-                    .cmp(b.get(&CubeColor::Blue).unwrap_or(&0))
-            })
-            .and_then(|cube| cube.get(&CubeColor::Blue))
-            .unwrap_or(&1);
+            .map(|game| (game, game.minimum_bag()))
+            .rev()
+            .max_by_key(|(_, bag)| bag.total())
+    }
 
-        minimum_red * minimum_green * minimum_blue
+    /// Every game that is not possible with `bag`, with the index of its first impossible draw.
+    pub fn impossible_games(&self, bag: &Bag) -> Vec<(&Game, usize)> {
+        self.0
+            .iter()
+            .filter_map(|game| Some((game, game.impossible_draw(bag)?)))
+            .collect()
+    }
+
+    /// Counts the game powers in `buckets` ranges of the same width, covering 0 to the largest
+    /// power.
+    pub fn power_histogram(&self, buckets: usize) -> Result<Vec<(Range<i64>, usize)>> {
+        let powers = self
+            .0
+            .iter()
+            .map(Game::power)
+            .collect::<Result<Vec<i64>>>()?;
+        let Some(&max) = powers.iter().max() else {
+            return Ok(vec![]);
+        };
+        let buckets = i64::try_from(buckets.max(1)).unwrap_or(i64::MAX);
+        let width = max / buckets + 1;
+        (0..buckets)
+            .map(|bucket| {
+                let (Some(start), Some(end)) =
+                    (bucket.checked_mul(width), (bucket + 1).checked_mul(width))
+                else {
+                    return Err(Error::NoSolution(
+                        "the power histogram doesn't fit in 64 bits",
+                    ));
+                };
+                let range = start..end;
+                let count = powers.iter().filter(|power| range.contains(power)).count();
+                Ok((range, count))
+            })
+            .collect()
     }
 }
 
-pub fn answer() -> (i32, i32) {
-    let max_red_cubes = 12;
-    let max_green_cubes = 13;
-    let max_blue_cubes = 14;
+const HISTOGRAM_BUCKETS: usize = 10;
+
+pub fn render_stats_text(games: &GameSet, bag: &Bag) -> Result<String> {
+    let mut text = format!("Games: {}\n\n", games.0.len());
+
+    writeln!(
+        text,
+        "{:<8}  {:>6}  {:>5}  {:>7}",
+        "Color", "Draws", "Max", "Mean"
+    )
+    .unwrap();
+    for stats in games.color_stats() {
+        writeln!(
+            text,
+            "{:<8}  {:>6}  {:>5}  {:>7.2}",
+            stats.color, stats.draws, stats.max, stats.mean
+        )
+        .unwrap();
+    }
 
-    let input = fs::read_to_string("puzzle2.txt").expect("File not found");
-    let games = input
-        .lines()
-        .map(|l| Game::try_from(l).unwrap())
-        .collect::<Vec<Game>>();
+    if let Some((game, minimum)) = games.largest_bag() {
+        writeln!(
+            text,
+            "\nLargest bag: game {} needs {} cubes ({minimum})",
+            game.id,
+            minimum.total()
+        )
+        .unwrap();
+    }
 
-    let sum_of_games: i32 = games
+    let impossible = games.impossible_games(bag);
+    writeln!(text, "\nImpossible with {bag}: {} games", impossible.len()).unwrap();
+    for (game, draw) in impossible {
+        writeln!(
+            text,
+            "  game {}: draw {} ({})",
+            game.id,
+            draw + 1,
+            Cubes(&game.cubes[draw])
+        )
+        .unwrap();
+    }
+
+    let histogram = games.power_histogram(HISTOGRAM_BUCKETS)?;
+    writeln!(text, "\nPowers:").unwrap();
+    for (range, count) in histogram {
+        let bar = format!(
+            "  {:>6}..{:<6} {count:>4} {}",
+            range.start,
+            range.end,
+            "#".repeat(count)
+        );
+        writeln!(text, "{}", bar.trim_end()).unwrap();
+    }
+    Ok(text)
+}
+
+fn json_cubes(set: &HashMap<CubeColor, i32>) -> String {
+    let cubes = sorted_cubes(set)
+        .into_iter()
+        .map(|(color, amount)| format!("{}:{amount}", json::string(color.name())))
+        .collect::<Vec<String>>()
+        .join(",");
+    format!("{{{cubes}}}")
+}
+
+/// Draws in `impossible` are 1-based, like in the text report.
+pub fn render_stats_json(games: &GameSet, bag: &Bag) -> Result<String> {
+    let colors = games
+        .color_stats()
         .iter()
-        .filter(|game| {
-            game.cubes.iter().all(|set| {
-                set.get(&CubeColor::Red) <= Some(&max_red_cubes)
-                    && set.get(&CubeColor::Green) <= Some(&max_green_cubes)
-                    && set.get(&CubeColor::Blue) <= Some(&max_blue_cubes)
-            })
+        .map(|stats| {
+            format!(
+                "{{\"color\":{},\"draws\":{},\"max\":{},\"mean\":{}}}",
+                json::string(stats.color.name()),
+                stats.draws,
+                stats.max,
+                stats.mean
+            )
         })
-        .map(|game| game.id)
-        .sum();
+        .collect::<Vec<String>>()
+        .join(",");
+    let largest_bag = games
+        .largest_bag()
+        .map_or(String::from("null"), |(game, minimum)| {
+            format!(
+                "{{\"game\":{},\"total\":{},\"cubes\":{}}}",
+                game.id,
+                minimum.total(),
+                json_cubes(&minimum.0)
+            )
+        });
+    let impossible = games
+        .impossible_games(bag)
+        .iter()
+        .map(|(game, draw)| {
+            format!(
+                "{{\"game\":{},\"draw\":{},\"cubes\":{}}}",
+                game.id,
+                draw + 1,
+                json_cubes(&game.cubes[*draw])
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+    let histogram = games
+        .power_histogram(HISTOGRAM_BUCKETS)?
+        .iter()
+        .map(|(range, count)| {
+            format!(
+                "{{\"from\":{},\"to\":{},\"count\":{count}}}",
+                range.start, range.end
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(",");
+
+    Ok(format!(
+        "{{\"games\":{},\"colors\":[{colors}],\"largest_bag\":{largest_bag},\
+        \"bag\":{},\"impossible\":[{impossible}],\"power_histogram\":[{histogram}]}}",
+        games.0.len(),
+        json_cubes(&bag.0)
+    ))
+}
+
+/// Part one checks the games against `bag`, the elf's bag by default.
+#[derive(Debug)]
+pub struct Puzzle2 {
+    pub bag: Bag,
+}
+
+impl Default for Puzzle2 {
+    fn default() -> Self {
+        Puzzle2 { bag: Bag::elf() }
+    }
+}
 
-    let power_of_games = games.iter().map(|game| game.power()).sum();
+impl Solution for Puzzle2 {
+    type Input = Vec<Game>;
+    type Answer = i64;
 
-    (sum_of_games, power_of_games)
+    fn parse(&self, input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Result<Option<Self::Answer>> {
+        Ok(Some(sum_possible_ids(input, &self.bag)))
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Result<Option<Self::Answer>> {
+        answer_part_2(input).map(Some)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::puzzle2::Game;
+    use std::collections::HashMap;
+
+    use crate::{
+        error::Error,
+        puzzle2::{
+            answer_part_1, answer_part_2, parse_input, render_stats_json, render_stats_text,
+            sum_possible_ids, Bag, Game, GameSet,
+        },
+        rng::Rng,
+    };
 
     use super::CubeColor;
 
@@ -181,14 +609,14 @@ mod tests {
         let game = Game::try_from(TEST_INPUT).unwrap();
 
         assert!(game.id == 1);
-        assert!(game.cubes[0].get(&CubeColor::Blue) == Some(&3));
-        assert!(game.cubes[0].get(&CubeColor::Red) == Some(&4));
+        assert!(game.cubes[0].get(&CubeColor::BLUE) == Some(&3));
+        assert!(game.cubes[0].get(&CubeColor::RED) == Some(&4));
 
-        assert!(game.cubes[1].get(&CubeColor::Red) == Some(&1));
-        assert!(game.cubes[1].get(&CubeColor::Green) == Some(&2));
-        assert!(game.cubes[1].get(&CubeColor::Blue) == Some(&6));
+        assert!(game.cubes[1].get(&CubeColor::RED) == Some(&1));
+        assert!(game.cubes[1].get(&CubeColor::GREEN) == Some(&2));
+        assert!(game.cubes[1].get(&CubeColor::BLUE) == Some(&6));
 
-        assert!(game.cubes[2].get(&CubeColor::Green) == Some(&2));
+        assert!(game.cubes[2].get(&CubeColor::GREEN) == Some(&2));
     }
 
     #[test]
@@ -197,18 +625,271 @@ mod tests {
         let cube_color = CubeColor::try_from(color_str);
 
         assert!(cube_color.is_ok());
-        assert!(cube_color.unwrap() == CubeColor::Blue);
+        assert!(cube_color.unwrap() == CubeColor::BLUE);
 
-        let wrong_color_str = "yellow";
+        let yellow = CubeColor::try_from("yellow").unwrap();
+        assert_eq!(yellow, CubeColor::named("yellow"));
+        assert_ne!(yellow, CubeColor::BLUE);
+        assert_eq!(yellow.to_string(), "yellow");
+
+        let wrong_color_str = "yell0w";
         let wrong_cube_color = CubeColor::try_from(wrong_color_str);
 
         assert!(wrong_cube_color.is_err())
     }
 
+    #[test]
+    fn test_parse_game_errors() {
+        let error = parse_input("Game 1: 3 blue\nGame 2: 4 yell0w").unwrap_err();
+
+        assert!(matches!(error, Error::UnknownColor { .. }));
+        assert_eq!(error.to_string(), "2:11: unknown cube color 'yell0w'");
+
+        assert!(matches!(
+            Game::try_from("Game x: 3 blue"),
+            Err(Error::BadNumber { .. })
+        ));
+        assert!(matches!(
+            Game::try_from("Game 1: blue"),
+            Err(Error::Malformed { .. })
+        ));
+        assert!(matches!(
+            Game::try_from("Game 1: 99999999999 blue"),
+            Err(Error::BadNumber { .. })
+        ));
+    }
+
+    fn parse_error(line: &str) -> String {
+        Game::try_from(line).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_parse_game_detailed_errors() {
+        assert_eq!(parse_error("Game x1: 3 blue"), "column 6: bad number 'x1'");
+        assert_eq!(
+            parse_error("Gaem 1: 3 blue"),
+            "column 1: expected `Game <id>`, found 'Gaem 1'"
+        );
+        assert_eq!(
+            parse_error("Game 1: 3 bl_ue"),
+            "column 11: unknown cube color 'bl_ue'"
+        );
+        assert_eq!(parse_error("Game 1: -3 blue"), "column 9: bad number '-3'");
+        assert_eq!(parse_error("Game 1: 3x blue"), "column 9: bad number '3x'");
+        assert_eq!(
+            parse_error("Game 1: 3 blue, 2 red, 1 blue"),
+            "column 26: cube color 'blue' drawn twice in one set"
+        );
+        assert_eq!(
+            parse_error("Game 1: 3 blue,"),
+            "column 16: missing `<amount> <color>`"
+        );
+        assert_eq!(
+            parse_error("Game 1: 3 blue;; 2 red"),
+            "column 16: missing `<amount> <color>`"
+        );
+        assert_eq!(
+            parse_error("Game 1: 3 blue 4"),
+            "column 9: expected `<amount> <color>`, found '3 blue 4'"
+        );
+        assert_eq!(
+            parse_input("Game 1: 2 red; 3 blue 4")
+                .unwrap_err()
+                .to_string(),
+            "1:16: expected `<amount> <color>`, found '3 blue 4'"
+        );
+        // The same color in different sets is fine
+        assert!(Game::try_from("Game 1: 3 blue; 1 blue").is_ok());
+    }
+
+    #[test]
+    fn test_game_is_possible_with_bag() {
+        let game = Game::try_from(TEST_INPUT).unwrap();
+
+        assert!(game.is_possible_with(&Bag::elf()));
+        assert!(game.is_possible_with(&Bag::parse("4 red, 2 green, 6 blue").unwrap()));
+        assert!(!game.is_possible_with(&Bag::parse("4 red, 2 green, 5 blue").unwrap()));
+        // A color missing from the bag can't be drawn
+        assert!(!game.is_possible_with(&Bag::parse("4 red, 6 blue").unwrap()));
+        assert_eq!(Bag::elf().limit(&CubeColor::GREEN), 13);
+        assert!(Bag::parse("12 red, 13 yell0w").is_err());
+    }
+
+    #[test]
+    fn test_extra_colors() {
+        let game =
+            Game::try_from("Game 7: 2 yellow, 1 red; 5 purple, 3 yellow, 2 green, 1 blue").unwrap();
+        let (yellow, purple) = (CubeColor::named("yellow"), CubeColor::named("purple"));
+
+        assert_eq!(game.cubes[1].get(&purple), Some(&5));
+        assert_eq!(game.minimum_bag().limit(&yellow), 3);
+        assert_eq!(game.power().unwrap(), 3 * 5 * 2);
+        assert!(!game.is_possible_with(&Bag::elf()));
+        assert!(game.is_possible_with(&Bag::elf().with(yellow, 3).with(purple, 5)));
+        assert!(CubeColor::named("purple") > CubeColor::named("amber"));
+        assert!(CubeColor::BLUE < CubeColor::named("amber"));
+    }
+
+    #[test]
+    fn test_display_game() {
+        let game = Game::try_from(TEST_INPUT).unwrap();
+        assert_eq!(
+            game.to_string(),
+            "Game 1: 4 red, 3 blue; 1 red, 2 green, 6 blue; 2 green"
+        );
+
+        let game = Game::try_from("Game 2: 1 purple, 2 blue, 3 amber").unwrap();
+        assert_eq!(game.to_string(), "Game 2: 2 blue, 3 amber, 1 purple");
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let colors = ["red", "green", "blue", "yellow", "purple", "cyan"].map(CubeColor::named);
+        let mut rng = Rng::new(0x5eed_2023);
+
+        for _ in 0..200 {
+            let cubes = (0..1 + rng.below(5))
+                .map(|_| {
+                    (0..1 + rng.below(colors.len()))
+                        .map(|_| {
+                            (
+                                colors[rng.below(colors.len())].clone(),
+                                rng.below(100) as i32,
+                            )
+                        })
+                        .collect::<HashMap<CubeColor, i32>>()
+                })
+                .collect();
+            let game = Game {
+                id: rng.below(1000) as i32,
+                cubes,
+            };
+
+            assert_eq!(Game::try_from(game.to_string().as_str()).unwrap(), game);
+        }
+    }
+
+    #[test]
+    fn test_display_rejected_games() {
+        // Games the parser refuses are still written out, but don't read back
+        let game = |id, cubes| Game { id, cubes };
+        let blue = |amount| HashMap::from([(CubeColor::BLUE, amount)]);
+        let rejected = [
+            (game(-1, vec![blue(3)]), "Game -1: 3 blue"),
+            (game(1, vec![]), "Game 1: "),
+            (game(1, vec![HashMap::new()]), "Game 1: "),
+            (game(1, vec![blue(3), HashMap::new()]), "Game 1: 3 blue; "),
+            (game(1, vec![blue(-3)]), "Game 1: -3 blue"),
+        ];
+
+        for (game, text) in rejected {
+            assert_eq!(game.to_string(), text);
+            assert!(Game::try_from(text).is_err(), "{text} was accepted");
+        }
+    }
+
+    const SAMPLE: &str = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green";
+
+    #[test]
+    fn test_game_set_queries() {
+        let games = GameSet::parse(SAMPLE).unwrap();
+
+        let stats = games.color_stats();
+        assert_eq!(
+            stats.iter().map(|s| s.color.clone()).collect::<Vec<_>>(),
+            CubeColor::STANDARD
+        );
+        assert_eq!((stats[0].draws, stats[0].max), (11, 20));
+        assert!((stats[0].mean - 61.0 / 11.0).abs() < 1e-9);
+
+        let (game, minimum) = games.largest_bag().unwrap();
+        assert_eq!((game.id, minimum.total()), (3, 39));
+
+        let impossible = games
+            .impossible_games(&Bag::elf())
+            .iter()
+            .map(|(game, draw)| (game.id, *draw))
+            .collect::<Vec<_>>();
+        assert_eq!(impossible, vec![(3, 0), (4, 2)]);
+
+        // Powers are 48, 12, 1560, 630 and 36
+        assert_eq!(
+            games.power_histogram(4).unwrap(),
+            vec![(0..391, 3), (391..782, 1), (782..1173, 0), (1173..1564, 1)]
+        );
+        assert!(GameSet(vec![]).power_histogram(4).unwrap().is_empty());
+        // A power of exactly i64::MAX leaves no room for the last bucket's end
+        let games =
+            GameSet::parse("Game 1: 3577 red, 42799 green, 92737 blue, 649657 yellow").unwrap();
+        assert_eq!(games.0[0].power().unwrap(), i64::MAX);
+        assert_eq!(
+            games.power_histogram(4).unwrap_err().to_string(),
+            "no solution: the power histogram doesn't fit in 64 bits"
+        );
+    }
+
+    #[test]
+    fn test_render_stats() {
+        let games = GameSet::parse(SAMPLE).unwrap();
+        let text = render_stats_text(&games, &Bag::elf()).unwrap();
+
+        assert!(text.contains("red           11     20     5.55\n"));
+        assert!(text.contains("Largest bag: game 3 needs 39 cubes (20 red, 13 green, 6 blue)"));
+        assert!(text.contains("  game 4: draw 3 (14 red, 3 green, 15 blue)\n"));
+
+        let json = render_stats_json(&games, &Bag::elf()).unwrap();
+        assert!(json.starts_with("{\"games\":5,\"colors\":[{\"color\":\"red\",\"draws\":11,"));
+        assert!(json.contains(
+            "\"impossible\":[{\"game\":3,\"draw\":1,\"cubes\":{\"red\":20,\"green\":8,\"blue\":6}},"
+        ));
+    }
+
+    #[test]
+    fn test_puzzle_answer_part_1() {
+        let games = parse_input(SAMPLE).unwrap();
+
+        assert_eq!(answer_part_1(&games).unwrap(), 8);
+        assert_eq!(
+            sum_possible_ids(&games, &Bag::parse("20 red, 13 green, 15 blue").unwrap()),
+            15
+        );
+    }
+
     #[test]
     fn test_game_power() {
         let game = Game::try_from(TEST_INPUT).unwrap();
 
-        assert!(game.power() == 48)
+        assert!(game.power().unwrap() == 48);
+
+        let game = Game::try_from("Game 1: 100000 red, 100000 green, 100000 blue").unwrap();
+        assert_eq!(game.power().unwrap(), 1_000_000_000_000_000);
+        assert_eq!(answer_part_2(&[game]).unwrap(), 1_000_000_000_000_000);
+
+        let game =
+            Game::try_from("Game 1: 2000000000 red, 2000000000 green, 2000000000 blue").unwrap();
+        assert!(matches!(game.power(), Err(Error::NoSolution(_))));
+        assert!(answer_part_2(&[game]).is_err());
+    }
+
+    #[test]
+    fn test_game_minimum_bag() {
+        let game = Game::try_from(TEST_INPUT).unwrap();
+
+        assert_eq!(
+            game.minimum_bag(),
+            Bag::default()
+                .with(CubeColor::RED, 4)
+                .with(CubeColor::GREEN, 2)
+                .with(CubeColor::BLUE, 6)
+        );
+        assert!(game.is_possible_with(&game.minimum_bag()));
+
+        let game = Game::try_from("Game 2: 3 blue, 4 red; 1 red, 6 blue").unwrap();
+        assert_eq!(game.minimum_bag().limit(&CubeColor::GREEN), 0);
+        assert_eq!(game.power().unwrap(), 0);
     }
 }
@@ -1,20 +1,36 @@
-use std::{collections::HashSet, fs, ops::Range};
+use std::{
+    fmt::Write,
+    ops::{Range, RangeInclusive},
+};
 
-use regex::Regex;
+use crate::{
+    error::{Error, Location, Result},
+    grid::{Grid, Point},
+    solution::Solution,
+};
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub struct DigitCapture {
-    pub row: isize,
-    pub column_range: Range<isize>,
+    pub row: usize,
+    pub column_range: Range<usize>,
     pub text: String,
     pub value: u32,
 }
 
 impl DigitCapture {
-    fn is_adjacent(&self, (row, column): (isize, isize)) -> bool {
-        let row_range = (self.row - 1)..(self.row + 2);
-        let column_range = (self.column_range.start - 1)..(self.column_range.end + 1);
-        row_range.contains(&row) && column_range.contains(&column)
+    /// Whether `point` touches one of the digits, diagonals included.
+    pub fn is_adjacent(&self, point: Point) -> bool {
+        point.row + 1 >= self.row
+            && point.row <= self.row + 1
+            && point.column + 1 >= self.column_range.start
+            && point.column <= self.column_range.end
+    }
+
+    /// The cells covered by the number.
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        self.column_range
+            .clone()
+            .map(|column| Point::new(self.row, column))
     }
 }
 
@@ -25,72 +41,206 @@ pub struct SymbolCapture {
     pub symbol: char,
 }
 
-pub fn parse_numbers(input: &str) -> Vec<DigitCapture> {
-    let number_regex = Regex::new(r"(\d+)").expect("Wrong regex pattern");
-    input
-        .lines()
-        .enumerate()
-        .map(|(idx, line)| (idx, number_regex.captures_iter(line)))
-        .flat_map(|(row, captures)| {
-            captures.map(move |capture| {
-                let regex_match = capture.get(1).expect("No match");
-                let text = regex_match.as_str().to_string();
-                let column_range =
-                    (regex_match.range().start as isize)..(regex_match.range().end as isize);
-                let value = text.parse::<u32>().expect("Can't parse number");
-                DigitCapture {
-                    row: row as isize,
-                    column_range,
-                    text,
-                    value,
-                }
-            })
+impl SymbolCapture {
+    pub fn point(&self) -> Point {
+        Point::new(self.row, self.column)
+    }
+}
+
+/// Anything but periods, digits, letters and whitespace is a symbol.
+pub fn is_symbol(c: char) -> bool {
+    !(c == '.' || c == '_' || c.is_alphanumeric() || c.is_whitespace())
+}
+
+pub fn parse_numbers(grid: &Grid<char>) -> Result<Vec<DigitCapture>> {
+    let mut numbers = vec![];
+    for (row, cells) in grid.rows().enumerate() {
+        let mut column = 0;
+        while column < cells.len() {
+            if !cells[column].is_ascii_digit() {
+                column += 1;
+                continue;
+            }
+            let start = column;
+            while column < cells.len() && cells[column].is_ascii_digit() {
+                column += 1;
+            }
+            let text = cells[start..column].iter().collect::<String>();
+            let value = text.parse::<u32>().map_err(|_| {
+                Error::bad_number(&text, Location::column(start + 1)).at_line(row + 1)
+            })?;
+            numbers.push(DigitCapture {
+                row,
+                column_range: start..column,
+                text,
+                value,
+            });
+        }
+    }
+    Ok(numbers)
+}
+
+pub fn parse_symbols(grid: &Grid<char>) -> Vec<SymbolCapture> {
+    grid.iter()
+        .filter(|(_, &c)| is_symbol(c))
+        .map(|(point, &symbol)| SymbolCapture {
+            row: point.row,
+            column: point.column,
+            symbol,
         })
         .collect()
 }
 
-pub fn parse_symbols(input: &str) -> Vec<SymbolCapture> {
-    let symbol_regex = Regex::new(r"([^\.\d\w\s])++").expect("Wrong regex pattern");
-
-    input
-        .lines()
-        .enumerate()
-        .map(|(idx, line)| (idx, symbol_regex.captures_iter(line)))
-        .flat_map(|(row, captures)| {
-            captures.map(move |capture| {
-                let regex_match = capture.get(1).expect("No match");
-                let column = regex_match.range().start;
-                let symbol = regex_match
-                    .as_str()
-                    .chars()
-                    .next()
-                    .expect("Empty symbol string");
-                SymbolCapture {
-                    row,
-                    column,
-                    symbol,
+/// The number covering every cell of the schematic, so the numbers around a symbol can be found
+/// by looking at its eight neighbours instead of checking every number.
+#[derive(Debug)]
+pub struct NumberIndex(Grid<Option<usize>>);
+
+impl NumberIndex {
+    /// `numbers` must lie inside a grid of the given size. Cells refer to numbers by index.
+    pub fn new(width: usize, height: usize, numbers: &[DigitCapture]) -> Self {
+        let mut cells = Grid::new(width, height, None);
+        for (idx, number) in numbers.iter().enumerate() {
+            for point in number.points() {
+                if let Some(cell) = cells.get_mut(point) {
+                    *cell = Some(idx);
                 }
-            })
-        })
-        .collect()
+            }
+        }
+        NumberIndex(cells)
+    }
+
+    pub fn number_at(&self, point: Point) -> Option<usize> {
+        self.0.get(point).copied().flatten()
+    }
+
+    /// Indices of the distinct numbers touching `point`, in reading order.
+    pub fn numbers_around(&self, point: Point) -> Vec<usize> {
+        let mut numbers = self
+            .0
+            .neighbours8(point)
+            .filter_map(|neighbour| self.number_at(neighbour))
+            .collect::<Vec<usize>>();
+        numbers.sort();
+        numbers.dedup();
+        numbers
+    }
+}
+
+/// How the numbers around a gear are combined into its ratio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Product,
+    Sum,
+}
+
+impl Aggregation {
+    pub fn apply(self, values: impl Iterator<Item = u32>) -> Result<u64> {
+        let mut values = values.map(u64::from);
+        match self {
+            Aggregation::Product => values.try_fold(1, u64::checked_mul),
+            Aggregation::Sum => values.try_fold(0, u64::checked_add),
+        }
+        .ok_or(Error::NoSolution("a gear ratio doesn't fit in 64 bits"))
+    }
+}
+
+/// Which symbols count as gears. The puzzle's rule is a `*` touching exactly two numbers, whose
+/// ratio is their product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GearRule {
+    pub symbols: Vec<char>,
+    /// Allowed number of adjacent numbers, both ends included.
+    pub numbers: RangeInclusive<usize>,
+    pub aggregation: Aggregation,
+}
+
+impl Default for GearRule {
+    fn default() -> Self {
+        GearRule {
+            symbols: vec!['*'],
+            numbers: 2..=2,
+            aggregation: Aggregation::Product,
+        }
+    }
+}
+
+// "2", "2..", "..3", "2..4" or "2..=4", written like the day ranges on the command line: "2..4"
+// leaves out 4, "2..=4" keeps it
+fn parse_count_range(text: &str) -> Option<RangeInclusive<usize>> {
+    let parse = |count: &str, default: usize| match count.trim() {
+        "" => Some(default),
+        count => count.parse::<usize>().ok(),
+    };
+    let range = match text.split_once("..") {
+        Some((min, max)) => match max.strip_prefix('=') {
+            Some(max) => parse(min, 0)?..=parse(max, usize::MAX)?,
+            None if max.trim().is_empty() => parse(min, 0)?..=usize::MAX,
+            None => parse(min, 0)?..=parse(max, 0)?.checked_sub(1)?,
+        },
+        None => {
+            let count = text.trim().parse::<usize>().ok()?;
+            count..=count
+        }
+    };
+    (!range.is_empty()).then_some(range)
 }
 
+impl GearRule {
+    /// Reads comma separated `key=value` settings, e.g. "symbols=*#, numbers=2.., ratio=sum".
+    /// Settings that are left out keep the puzzle's rule.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut rule = GearRule::default();
+        for setting in spec.split(',').filter(|s| !s.trim().is_empty()) {
+            let error = || {
+                Error::malformed(
+                    "`symbols=<chars>`, `numbers=<count or range>` or `ratio=product|sum`",
+                    setting.trim(),
+                    Location::default(),
+                )
+            };
+            let (key, value) = setting.split_once('=').ok_or_else(error)?;
+            match (key.trim(), value.trim()) {
+                ("symbols", symbols) if !symbols.is_empty() => {
+                    rule.symbols = symbols.chars().collect();
+                }
+                ("numbers", count) => rule.numbers = parse_count_range(count).ok_or_else(error)?,
+                ("ratio", "product") => rule.aggregation = Aggregation::Product,
+                ("ratio", "sum") => rule.aggregation = Aggregation::Sum,
+                _ => return Err(error()),
+            }
+        }
+        // The product of no numbers would give a lone symbol a ratio of 1
+        if rule.aggregation == Aggregation::Product && rule.numbers.contains(&0) {
+            return Err(Error::malformed(
+                "at least one number around a gear with `ratio=product`",
+                spec.trim(),
+                Location::default(),
+            ));
+        }
+        Ok(rule)
+    }
+
+    pub fn matches(&self, symbol: char, adjacent_numbers: usize) -> bool {
+        self.symbols.contains(&symbol) && self.numbers.contains(&adjacent_numbers)
+    }
+
+    pub fn ratio<'a>(&self, numbers: impl IntoIterator<Item = &'a DigitCapture>) -> Result<u64> {
+        self.aggregation
+            .apply(numbers.into_iter().map(|number| number.value))
+    }
+}
+
+/// Checks every number against every symbol. [`Schematic::gears`] gives the same gears through
+/// the [`NumberIndex`].
 pub fn find_gears<'a>(
     numbers: &'a [DigitCapture],
     symbols: &'a [SymbolCapture],
+    rule: &GearRule,
 ) -> Vec<&'a SymbolCapture> {
     symbols
         .iter()
-        .filter(|capture| {
-            capture.symbol == '*'
-                && numbers
-                    .iter()
-                    .filter(|number| {
-                        number.is_adjacent((capture.row as isize, capture.column as isize))
-                    })
-                    .count()
-                    == 2
-        })
+        .filter(|capture| rule.matches(capture.symbol, find_adjacents(capture, numbers).len()))
         .collect::<Vec<&'a SymbolCapture>>()
 }
 
@@ -100,58 +250,410 @@ pub fn find_adjacents<'a>(
 ) -> Vec<&'a DigitCapture> {
     numbers
         .iter()
-        .filter(|number| number.is_adjacent((symbol.row as isize, symbol.column as isize)))
+        .filter(|number| number.is_adjacent(symbol.point()))
         .collect()
 }
 
-pub fn answer() -> (i32, i32) {
-    let input = fs::read_to_string("puzzle3.txt").expect("File not found.");
+/// A parsed schematic. The fields stay private so the number index can't fall out of step with
+/// the numbers it points into.
+pub struct Schematic {
+    grid: Grid<char>,
+    numbers: Vec<DigitCapture>,
+    symbols: Vec<SymbolCapture>,
+    index: NumberIndex,
+}
+
+impl Schematic {
+    pub fn grid(&self) -> &Grid<char> {
+        &self.grid
+    }
 
-    // Get number locations with slices
-    let digit_captures: Vec<DigitCapture> = parse_numbers(&input);
+    pub fn numbers(&self) -> &[DigitCapture] {
+        &self.numbers
+    }
 
-    // Get every symbol
-    let symbol_captures: Vec<SymbolCapture> = parse_symbols(&input);
+    pub fn symbols(&self) -> &[SymbolCapture] {
+        &self.symbols
+    }
 
-    let mut adjacent_numbers = HashSet::<&DigitCapture>::new();
+    pub fn adjacent_numbers(&self, point: Point) -> Vec<&DigitCapture> {
+        self.index
+            .numbers_around(point)
+            .into_iter()
+            .map(|idx| &self.numbers[idx])
+            .collect()
+    }
 
-    for symbol in &symbol_captures {
-        digit_captures
+    /// The symbols that are gears under `rule`, with the numbers around them.
+    pub fn gears(&self, rule: &GearRule) -> Vec<(&SymbolCapture, Vec<&DigitCapture>)> {
+        self.symbols
             .iter()
-            .filter(|number| number.is_adjacent((symbol.row as isize, symbol.column as isize)))
-            .for_each(|number| {
-                adjacent_numbers.insert(number);
-            });
+            .filter(|symbol| rule.symbols.contains(&symbol.symbol))
+            .map(|symbol| (symbol, self.adjacent_numbers(symbol.point())))
+            .filter(|(symbol, numbers)| rule.matches(symbol.symbol, numbers.len()))
+            .collect()
     }
 
-    let answer_part1: i32 = adjacent_numbers
+    /// Whether any cell around the number holds a symbol.
+    pub fn is_part_number(&self, number: &DigitCapture) -> bool {
+        number
+            .points()
+            .flat_map(|point| self.grid.neighbours8(point))
+            .any(|neighbour| is_symbol(self.grid[neighbour]))
+    }
+}
+
+pub fn parse_input(input: &str) -> Result<Schematic> {
+    // Rows shorter than the longest one end in empty cells
+    let grid = Grid::parse_padded(input, '.');
+    let numbers = parse_numbers(&grid)?;
+    Ok(Schematic {
+        index: NumberIndex::new(grid.width(), grid.height(), &numbers),
+        symbols: parse_symbols(&grid),
+        numbers,
+        grid,
+    })
+}
+
+pub fn answer_part_1(schematic: &Schematic) -> Result<u64> {
+    Ok(schematic
+        .numbers
         .iter()
-        .fold(0, |acc, number| acc + number.value) as i32;
+        .filter(|number| schematic.is_part_number(number))
+        .map(|number| u64::from(number.value))
+        .sum())
+}
+
+pub fn sum_gear_ratios(schematic: &Schematic, rule: &GearRule) -> Result<u64> {
+    schematic
+        .gears(rule)
+        .into_iter()
+        .try_fold(0_u64, |sum, (_, numbers)| {
+            sum.checked_add(rule.ratio(numbers)?)
+                .ok_or(Error::NoSolution(
+                    "the sum of gear ratios doesn't fit in 64 bits",
+                ))
+        })
+}
+
+pub fn answer_part_2(schematic: &Schematic) -> Result<u64> {
+    sum_gear_ratios(schematic, &GearRule::default())
+}
+
+/// How a cell of the schematic is drawn by the renderers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    /// Background and anything else that is neither a number nor a symbol.
+    Plain,
+    /// A digit of a number next to a symbol.
+    PartNumber,
+    /// A digit of a number with no symbol around it.
+    OtherNumber,
+    /// A symbol that is not a gear.
+    Symbol,
+    Gear,
+    /// A digit of a number counted in the ratio of a gear.
+    GearNumber,
+}
+
+impl Highlight {
+    const ALL: [Highlight; 6] = [
+        Highlight::Plain,
+        Highlight::PartNumber,
+        Highlight::OtherNumber,
+        Highlight::Symbol,
+        Highlight::Gear,
+        Highlight::GearNumber,
+    ];
+
+    /// The highlights worth explaining, with their names.
+    fn legend() -> impl Iterator<Item = (Highlight, &'static str)> {
+        Highlight::ALL
+            .into_iter()
+            .filter_map(|highlight| Some((highlight, highlight.label()?)))
+    }
+
+    fn label(self) -> Option<&'static str> {
+        match self {
+            Highlight::Plain => None,
+            Highlight::PartNumber => Some("part number"),
+            Highlight::OtherNumber => Some("not a part"),
+            Highlight::Symbol => Some("symbol"),
+            Highlight::Gear => Some("gear"),
+            Highlight::GearNumber => Some("gear number"),
+        }
+    }
+
+    fn ansi(self) -> Option<&'static str> {
+        match self {
+            Highlight::Plain => None,
+            Highlight::PartNumber => Some("\x1b[32m"),
+            Highlight::OtherNumber => Some("\x1b[31m"),
+            Highlight::Symbol => Some("\x1b[33m"),
+            Highlight::Gear => Some("\x1b[1;35m"),
+            Highlight::GearNumber => Some("\x1b[1;36m"),
+        }
+    }
+
+    fn class(self) -> &'static str {
+        match self {
+            Highlight::Plain => "plain",
+            Highlight::PartNumber => "part",
+            Highlight::OtherNumber => "other",
+            Highlight::Symbol => "symbol",
+            Highlight::Gear => "gear",
+            Highlight::GearNumber => "gear-number",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            Highlight::Plain => "#5c5c70",
+            Highlight::PartNumber => "#3fb950",
+            Highlight::OtherNumber => "#f85149",
+            Highlight::Symbol => "#d29922",
+            Highlight::Gear => "#db61a2",
+            Highlight::GearNumber => "#39c5cf",
+        }
+    }
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+const BACKGROUND: &str = "#0f0f23";
+const SVG_CELL_WIDTH: usize = 9;
+const SVG_CELL_HEIGHT: usize = 16;
+
+fn mark(
+    cells: &mut Grid<Highlight>,
+    points: impl IntoIterator<Item = Point>,
+    highlight: Highlight,
+) {
+    for point in points {
+        if let Some(cell) = cells.get_mut(point) {
+            *cell = highlight;
+        }
+    }
+}
+
+/// Classifies every cell of the schematic. Gears and their numbers win over plain symbols and
+/// part numbers.
+pub fn highlights(schematic: &Schematic, rule: &GearRule) -> Grid<Highlight> {
+    let mut cells = Grid::new(
+        schematic.grid.width(),
+        schematic.grid.height(),
+        Highlight::Plain,
+    );
+    for symbol in &schematic.symbols {
+        mark(&mut cells, [symbol.point()], Highlight::Symbol);
+    }
+    for number in &schematic.numbers {
+        let highlight = if schematic.is_part_number(number) {
+            Highlight::PartNumber
+        } else {
+            Highlight::OtherNumber
+        };
+        mark(&mut cells, number.points(), highlight);
+    }
+    for (gear, numbers) in schematic.gears(rule) {
+        mark(&mut cells, [gear.point()], Highlight::Gear);
+        for number in numbers {
+            mark(&mut cells, number.points(), Highlight::GearNumber);
+        }
+    }
+    cells
+}
 
-    ////////////////
+/// The schematic with ANSI colors for a terminal, followed by a legend.
+pub fn render_ansi(schematic: &Schematic, rule: &GearRule) -> String {
+    let highlights = highlights(schematic, rule);
+    let mut text = String::new();
 
-    let gears = find_gears(&digit_captures, &symbol_captures);
+    for (chars, row) in schematic.grid.rows().zip(highlights.rows()) {
+        let mut current = Highlight::Plain;
+        for (&c, &highlight) in chars.iter().zip(row) {
+            if highlight != current {
+                if current != Highlight::Plain {
+                    text.push_str(ANSI_RESET);
+                }
+                text.push_str(highlight.ansi().unwrap_or_default());
+                current = highlight;
+            }
+            text.push(c);
+        }
+        if current != Highlight::Plain {
+            text.push_str(ANSI_RESET);
+        }
+        text.push('\n');
+    }
+
+    text.push('\n');
+    let legend = Highlight::legend()
+        .map(|(highlight, label)| {
+            format!(
+                "{}{label}{ANSI_RESET}",
+                highlight.ansi().unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>();
+    writeln!(text, "{}", legend.join("  ")).unwrap();
+    text
+}
 
-    let gear_ratios_sum: u32 = gears
+/// The schematic as it was read, for output that can't show colors.
+pub fn render_plain(schematic: &Schematic) -> String {
+    let mut text = String::new();
+    for chars in schematic.grid.rows() {
+        text.extend(chars);
+        text.push('\n');
+    }
+    text
+}
+
+fn push_escaped(text: &mut String, c: char) {
+    match c {
+        '&' => text.push_str("&amp;"),
+        '<' => text.push_str("&lt;"),
+        '>' => text.push_str("&gt;"),
+        '"' => text.push_str("&quot;"),
+        _ => text.push(c),
+    }
+}
+
+fn style_rules(property: &str) -> String {
+    Highlight::ALL
         .iter()
-        .map(|gear| {
-            find_adjacents(gear, &digit_captures)
-                .iter()
-                .map(|number| number.value)
-                .product::<u32>()
+        .map(|highlight| {
+            format!(
+                ".{} {{ {property}: {} }}\n",
+                highlight.class(),
+                highlight.color()
+            )
         })
-        .sum();
+        .collect()
+}
+
+/// A standalone HTML page showing the schematic in a `<pre>` block, one `<span>` per run of
+/// cells drawn the same way.
+pub fn render_html(schematic: &Schematic, rule: &GearRule) -> String {
+    let highlights = highlights(schematic, rule);
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Engine schematic</title>\n<style>\n",
+    );
+    writeln!(html, "pre {{ background: {BACKGROUND}; padding: 1em; }}").unwrap();
+    html.push_str(&style_rules("color"));
+    html.push_str(".gear, .gear-number { font-weight: bold; }\n</style>\n</head>\n<body>\n<p>");
+    for (highlight, label) in Highlight::legend() {
+        write!(
+            html,
+            "<span class=\"{}\">{label}</span> ",
+            highlight.class()
+        )
+        .unwrap();
+    }
+    html.push_str("</p>\n<pre>");
+
+    for (chars, row) in schematic.grid.rows().zip(highlights.rows()) {
+        let mut start = 0;
+        while start < chars.len() {
+            let highlight = row[start];
+            let end = row[start..]
+                .iter()
+                .position(|&other| other != highlight)
+                .map_or(chars.len(), |len| start + len);
+            write!(html, "<span class=\"{}\">", highlight.class()).unwrap();
+            for &c in &chars[start..end] {
+                push_escaped(&mut html, c);
+            }
+            html.push_str("</span>");
+            start = end;
+        }
+        html.push('\n');
+    }
+
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+/// An SVG image of the schematic with one `<text>` element per cell. The `.` background cells
+/// are left out to keep large schematics small.
+pub fn render_svg(schematic: &Schematic, rule: &GearRule) -> String {
+    let highlights = highlights(schematic, rule);
+    let width = schematic.grid.width() * SVG_CELL_WIDTH;
+    let height = schematic.grid.height() * SVG_CELL_HEIGHT;
+    let mut svg = String::new();
+
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         viewBox=\"0 0 {width} {height}\" font-family=\"monospace\" font-size=\"14\" \
+         text-anchor=\"middle\">"
+    )
+    .unwrap();
+    svg.push_str("<style>\n");
+    svg.push_str(&style_rules("fill"));
+    svg.push_str(".gear, .gear-number { font-weight: bold; }\n</style>\n");
+    writeln!(
+        svg,
+        "<rect width=\"{width}\" height=\"{height}\" fill=\"{BACKGROUND}\"/>"
+    )
+    .unwrap();
+
+    for (point, &c) in schematic.grid.iter() {
+        if c == '.' {
+            continue;
+        }
+        write!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" class=\"{}\">",
+            point.column * SVG_CELL_WIDTH + SVG_CELL_WIDTH / 2,
+            (point.row + 1) * SVG_CELL_HEIGHT - 4,
+            highlights[point].class()
+        )
+        .unwrap();
+        push_escaped(&mut svg, c);
+        svg.push_str("</text>\n");
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
 
-    let answer_part2: i32 = gear_ratios_sum as i32;
+/// Part two sums the ratios of the gears picked by `gears`, the puzzle's rule by default.
+#[derive(Debug, Default)]
+pub struct Puzzle3 {
+    pub gears: GearRule,
+}
+
+impl Solution for Puzzle3 {
+    type Input = Schematic;
+    type Answer = u64;
+
+    fn parse(&self, input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Result<Option<Self::Answer>> {
+        answer_part_1(input).map(Some)
+    }
 
-    (answer_part1, answer_part2)
+    fn part_two(&self, input: &Self::Input) -> Result<Option<Self::Answer>> {
+        sum_gear_ratios(input, &self.gears).map(Some)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::ops::Range;
 
-    use crate::puzzle3::{parse_numbers, parse_symbols, DigitCapture, SymbolCapture};
+    use crate::{
+        grid::{Grid, Point},
+        puzzle3::{
+            answer_part_1, answer_part_2, find_adjacents, find_gears, highlights, parse_input,
+            parse_numbers, parse_symbols, render_ansi, render_html, render_plain, render_svg,
+            sum_gear_ratios, Aggregation, DigitCapture, GearRule, Highlight, SymbolCapture,
+        },
+    };
 
     const TEST_INPUT: &str = r#"467..114..
 ...*......
@@ -166,7 +668,8 @@ mod tests {
 
     #[test]
     fn test_parse_numbers() {
-        let digit_captures: Vec<DigitCapture> = parse_numbers(TEST_INPUT);
+        let grid = Grid::parse_chars(TEST_INPUT).unwrap();
+        let digit_captures: Vec<DigitCapture> = parse_numbers(&grid).unwrap();
 
         assert!(
             digit_captures.first()
@@ -179,9 +682,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_number_overflow() {
+        let grid = Grid::parse_padded("..1..\n.99999999999.", '.');
+        let error = parse_numbers(&grid).unwrap_err();
+
+        assert_eq!(error.to_string(), "2:2: bad number '99999999999'");
+    }
+
+    #[test]
+    fn test_parse_ragged_rows() {
+        let ragged = TEST_INPUT
+            .lines()
+            .map(|line| line.trim_end_matches('.'))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let schematic = parse_input(&ragged).unwrap();
+
+        // `..35..633` is the longest row once trailing dots are gone
+        assert_eq!(schematic.grid.width(), 9);
+        assert_eq!(answer_part_1(&schematic).unwrap(), 4361);
+        assert_eq!(answer_part_2(&schematic).unwrap(), 467835);
+    }
+
     #[test]
     fn test_parse_symbols() {
-        let symbol_capture: Vec<SymbolCapture> = parse_symbols(TEST_INPUT);
+        let grid = Grid::parse_chars(TEST_INPUT).unwrap();
+        let symbol_capture: Vec<SymbolCapture> = parse_symbols(&grid);
 
         assert!(
             symbol_capture.first()
@@ -191,5 +718,165 @@ mod tests {
                     symbol: '*'
                 })
         );
+        assert_eq!(symbol_capture.len(), 6);
+    }
+
+    #[test]
+    fn test_is_adjacent() {
+        let number = DigitCapture {
+            row: 1,
+            column_range: 2..4,
+            text: String::from("35"),
+            value: 35,
+        };
+
+        assert!(number.is_adjacent(Point::new(0, 1)));
+        assert!(number.is_adjacent(Point::new(2, 4)));
+        assert!(!number.is_adjacent(Point::new(3, 3)));
+        assert!(!number.is_adjacent(Point::new(1, 5)));
+        assert!(!number.is_adjacent(Point::new(0, 0)));
+    }
+
+    #[test]
+    fn test_number_index() {
+        let schematic = parse_input(TEST_INPUT).unwrap();
+
+        assert_eq!(schematic.index.number_at(Point::new(0, 1)), Some(0));
+        assert_eq!(schematic.index.number_at(Point::new(0, 3)), None);
+        assert_eq!(schematic.index.numbers_around(Point::new(1, 3)), vec![0, 2]);
+        assert!(schematic.index.numbers_around(Point::new(9, 9)).is_empty());
+
+        for symbol in &schematic.symbols {
+            assert_eq!(
+                schematic.adjacent_numbers(symbol.point()),
+                find_adjacents(symbol, &schematic.numbers)
+            );
+        }
+        assert_eq!(
+            schematic
+                .gears(&GearRule::default())
+                .iter()
+                .map(|(gear, _)| *gear)
+                .collect::<Vec<_>>(),
+            find_gears(&schematic.numbers, &schematic.symbols, &GearRule::default())
+        );
+    }
+
+    #[test]
+    fn test_gear_rules() {
+        let schematic = parse_input(TEST_INPUT).unwrap();
+
+        // Every symbol touching at least one number, with the sum of its numbers
+        let rule = GearRule::parse("symbols=*#+$, numbers=1.., ratio=sum").unwrap();
+        assert_eq!(sum_gear_ratios(&schematic, &rule).unwrap(), 4361);
+        assert_eq!(
+            sum_gear_ratios(&schematic, &rule).unwrap(),
+            find_gears(&schematic.numbers, &schematic.symbols, &rule)
+                .iter()
+                .map(|gear| rule
+                    .ratio(find_adjacents(gear, &schematic.numbers))
+                    .unwrap())
+                .sum::<u64>()
+        );
+
+        // The lonely `*` next to 617
+        let rule = GearRule::parse("numbers=1..2").unwrap();
+        assert_eq!(sum_gear_ratios(&schematic, &rule).unwrap(), 617);
+
+        assert_eq!(GearRule::parse("").unwrap(), GearRule::default());
+        assert_eq!(
+            GearRule::parse("numbers=2..=3, ratio=sum").unwrap(),
+            GearRule {
+                symbols: vec!['*'],
+                numbers: 2..=3,
+                aggregation: Aggregation::Sum
+            }
+        );
+        assert_eq!(GearRule::parse("numbers=2..4").unwrap().numbers, 2..=3);
+        assert_eq!(
+            GearRule::parse("numbers=2..").unwrap().numbers,
+            2..=usize::MAX
+        );
+        assert!(GearRule::parse("numbers=3..2").is_err());
+        assert!(GearRule::parse("numbers=2..2").is_err());
+        assert!(GearRule::parse("numbers=..1, ratio=sum").is_ok());
+        assert_eq!(
+            GearRule::parse("numbers=..2").unwrap_err().to_string(),
+            "expected at least one number around a gear with `ratio=product`, found 'numbers=..2'"
+        );
+        assert!(GearRule::parse("ratio=max").is_err());
+        assert!(GearRule::parse("symbols").is_err());
+    }
+
+    #[test]
+    fn test_gear_ratio_overflow() {
+        let schematic = parse_input("1000000.1000000\n1000000*1000000").unwrap();
+        let rule = GearRule::parse("numbers=4").unwrap();
+
+        assert_eq!(
+            sum_gear_ratios(&schematic, &rule).unwrap_err().to_string(),
+            "no solution: a gear ratio doesn't fit in 64 bits"
+        );
+    }
+
+    #[test]
+    fn test_highlights() {
+        let schematic = parse_input(TEST_INPUT).unwrap();
+        let cells = highlights(&schematic, &GearRule::default());
+
+        assert_eq!(cells[Point::new(0, 0)], Highlight::GearNumber);
+        assert_eq!(cells[Point::new(0, 5)], Highlight::OtherNumber);
+        assert_eq!(cells[Point::new(1, 3)], Highlight::Gear);
+        assert_eq!(cells[Point::new(2, 6)], Highlight::PartNumber);
+        assert_eq!(cells[Point::new(4, 3)], Highlight::Symbol);
+        assert_eq!(cells[Point::new(5, 7)], Highlight::OtherNumber);
+        assert_eq!(cells[Point::new(9, 9)], Highlight::Plain);
+    }
+
+    #[test]
+    fn test_render_ansi() {
+        let schematic = parse_input(TEST_INPUT).unwrap();
+        let text = render_ansi(&schematic, &GearRule::default());
+        let first_line = text.lines().next().unwrap();
+
+        assert_eq!(first_line, "\x1b[1;36m467\x1b[0m..\x1b[31m114\x1b[0m..");
+        assert_eq!(text.lines().count(), 12);
+        assert!(text.ends_with("\x1b[1;36mgear number\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_render_plain() {
+        let schematic = parse_input(TEST_INPUT).unwrap();
+
+        assert_eq!(render_plain(&schematic), format!("{TEST_INPUT}\n"));
+    }
+
+    #[test]
+    fn test_render_html_and_svg() {
+        let schematic = parse_input("12&.\n...<").unwrap();
+        let rule = GearRule::default();
+
+        let html = render_html(&schematic, &rule);
+        assert!(html.contains(
+            "<pre><span class=\"part\">12</span><span class=\"symbol\">&amp;</span>\
+             <span class=\"plain\">.</span>\n<span class=\"plain\">...</span>\
+             <span class=\"symbol\">&lt;</span>\n</pre>"
+        ));
+
+        let svg = render_svg(&schematic, &rule);
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"36\" height=\"32\"")
+        );
+        assert!(svg.contains("<text x=\"22\" y=\"12\" class=\"symbol\">&amp;</text>"));
+        assert_eq!(svg.matches("<text ").count(), 4);
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_puzzle_answers() {
+        let schematic = parse_input(TEST_INPUT).unwrap();
+
+        assert_eq!(answer_part_1(&schematic).unwrap(), 4361);
+        assert_eq!(answer_part_2(&schematic).unwrap(), 467835);
     }
 }
@@ -1,6 +1,7 @@
-use std::fs;
-
-use regex::Regex;
+use crate::{
+    error::{column_of, Error, Location, Result},
+    solution::Solution,
+};
 
 #[derive(Clone, Debug)]
 pub struct Scratchcard {
@@ -9,25 +10,23 @@ pub struct Scratchcard {
     pub owning_numbers: Vec<u32>,
 }
 
-#[derive(Debug, Clone)]
-pub struct ScratchcardError(&'static str);
-
 impl Scratchcard {
-    fn parse_numbers_string(
-        number_match_regex: &Regex,
-        number_str: &str,
-    ) -> Result<Vec<u32>, ScratchcardError> {
-        number_match_regex
-            .captures_iter(number_str)
+    // Every whitespace separated token has to be a number
+    fn parse_numbers_string(card_str: &str, number_str: &str) -> Result<Vec<u32>> {
+        number_str
+            .split_whitespace()
             .map(|number| {
-                number
-                    .get(1)
-                    .unwrap()
-                    .as_str()
-                    .parse::<u32>()
-                    .map_err(|_| ScratchcardError("Can't parse numbers from scratchcard string."))
+                number.parse::<u32>().map_err(|_| {
+                    Error::bad_number(
+                        number,
+                        Location {
+                            line: None,
+                            column: column_of(card_str, number),
+                        },
+                    )
+                })
             })
-            .collect::<Result<Vec<u32>, ScratchcardError>>()
+            .collect::<Result<Vec<u32>>>()
     }
 
     pub fn get_score(&self) -> u32 {
@@ -49,85 +48,109 @@ impl Scratchcard {
 }
 
 impl TryFrom<&str> for Scratchcard {
-    type Error = ScratchcardError;
+    type Error = Error;
 
     // Expecting string in the format "Card ##: (#+)+ | (#+)+"
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let Some((_, card_numbers_str)) = value.split_once(':') else {
-            return Err(ScratchcardError("Can't parse scratchcard string."));
+    fn try_from(value: &str) -> Result<Self> {
+        let Some((card_str, card_numbers_str)) = value.split_once(':') else {
+            return Err(Error::malformed(
+                "`Card <id>: <numbers> | <numbers>`",
+                value,
+                Location::default(),
+            ));
         };
 
-        let number_regex = Regex::new(r"(\d+)").expect("");
-
-        let Some(card_numbers) =
-            card_numbers_str
-                .split_once('|')
-                .map(|(winning_numbers, picked_numbers)| {
-                    let winning = Scratchcard::parse_numbers_string(&number_regex, winning_numbers);
-                    let picked = Scratchcard::parse_numbers_string(&number_regex, picked_numbers);
-                    (winning, picked)
-                })
-        else {
-            return Err(ScratchcardError("Can't parse scratchcard numbers."));
+        let id_str = card_str.trim_start_matches("Card").trim();
+        let id = id_str.parse::<u32>().map_err(|_| {
+            Error::bad_number(
+                id_str,
+                Location {
+                    line: None,
+                    column: column_of(value, id_str),
+                },
+            )
+        })?;
+
+        let Some((winning_numbers, picked_numbers)) = card_numbers_str.split_once('|') else {
+            return Err(Error::missing(
+                "`|` between winning and owned numbers",
+                Location::default(),
+            ));
         };
 
-        match card_numbers {
-            (Ok(winning_numbers), Ok(owning_numbers)) => Ok(Scratchcard {
-                id: 0,
-                winning_numbers,
-                owning_numbers,
-            }),
-            (Err(e), _) | (_, Err(e)) => Err(e),
-        }
+        Ok(Scratchcard {
+            id,
+            winning_numbers: Scratchcard::parse_numbers_string(value, winning_numbers)?,
+            owning_numbers: Scratchcard::parse_numbers_string(value, picked_numbers)?,
+        })
     }
 }
 
-pub fn answer_part_1(scratchcards: &[Result<Scratchcard, ScratchcardError>]) -> i32 {
+pub fn answer_part_1(scratchcards: &[Scratchcard]) -> Result<i32> {
     // Sum all scores of every scratchcard
-    scratchcards
+    Ok(scratchcards
         .iter()
-        .map(|card| card.as_ref().map(|c| c.get_score()))
-        .fold(0, |acc, score| acc + score.unwrap_or(0)) as i32
+        .fold(0, |acc, card| acc + card.get_score()) as i32)
 }
 
-pub fn answer_part_2(scratchcards: &[Result<Scratchcard, ScratchcardError>]) -> i32 {
-    let mut card_indexes: Vec<usize> = scratchcards
+/// Number of copies held of every scratchcard, counting the original, once every won copy has
+/// been scratched. Cards only win copies of the cards after them, so one pass is enough.
+pub fn card_copies(scratchcards: &[Scratchcard]) -> Result<Vec<u64>> {
+    let mut copies = vec![1_u64; scratchcards.len()];
+
+    for (index, card) in scratchcards.iter().enumerate() {
+        let last_won = (index + card.get_matching_count() as usize).min(scratchcards.len() - 1);
+        for won in (index + 1)..=last_won {
+            copies[won] = copies[won]
+                .checked_add(copies[index])
+                .ok_or(Error::NoSolution(
+                    "the number of scratchcards doesn't fit in 64 bits",
+                ))?;
+        }
+    }
+    Ok(copies)
+}
+
+pub fn answer_part_2(scratchcards: &[Scratchcard]) -> Result<u64> {
+    card_copies(scratchcards)?
         .iter()
-        .enumerate()
-        .map(|(idx, _)| idx)
-        .collect();
+        .try_fold(0_u64, |total, &copies| total.checked_add(copies))
+        .ok_or(Error::NoSolution(
+            "the number of scratchcards doesn't fit in 64 bits",
+        ))
+}
 
-    let mut card_index = 0;
+pub fn parse_input(input: &str) -> Result<Vec<Scratchcard>> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| Scratchcard::try_from(line).map_err(|e| e.at_line(idx + 1)))
+        .collect::<Result<Vec<Scratchcard>>>()
+}
 
-    while card_index < card_indexes.len() {
-        let index = card_indexes[card_index];
-        let card = scratchcards[index].as_ref().expect("Wrong scratchcard");
-        let matching_count = card.get_matching_count();
+pub struct Puzzle4;
 
-        for i in (index + 1)..=(index + matching_count as usize) {
-            card_indexes.push(i);
-        }
+impl Solution for Puzzle4 {
+    type Input = Vec<Scratchcard>;
+    type Answer = u64;
 
-        card_index += 1
+    fn parse(&self, input: &str) -> Result<Self::Input> {
+        parse_input(input)
     }
-    card_indexes.len() as i32
-}
 
-pub fn answer() -> (i32, i32) {
-    let input = fs::read_to_string("puzzle4.txt").expect("Puzzle file not found.");
-
-    let scratchcards = input
-        .lines()
-        .map(Scratchcard::try_from)
-        .collect::<Vec<Result<Scratchcard, ScratchcardError>>>();
+    fn part_one(&self, input: &Self::Input) -> Result<Option<Self::Answer>> {
+        answer_part_1(input).map(|score| Some(score as u64))
+    }
 
-    (answer_part_1(&scratchcards), answer_part_2(&scratchcards))
+    fn part_two(&self, input: &Self::Input) -> Result<Option<Self::Answer>> {
+        answer_part_2(input).map(Some)
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::puzzle4::{answer_part_1, answer_part_2, Scratchcard, ScratchcardError};
+    use crate::puzzle4::{answer_part_1, answer_part_2, card_copies, parse_input, Scratchcard};
 
     #[test]
     fn test_parse_scorecard() {
@@ -135,7 +158,19 @@ mod tests {
 
         let scratchcard = Scratchcard::try_from(card_input);
 
-        assert!(scratchcard.is_ok())
+        assert!(scratchcard.is_ok());
+        assert_eq!(scratchcard.unwrap().id, 1);
+    }
+
+    #[test]
+    fn test_parse_scorecard_errors() {
+        let puzzle_input = "Card 1: 41 48 | 83 86\nCard 2: 13 x3 | 61 30";
+
+        let error = parse_input(puzzle_input).unwrap_err();
+
+        assert_eq!(error.to_string(), "2:12: bad number 'x3'");
+        assert!(Scratchcard::try_from("Card 3: 1 2 3").is_err());
+        assert!(Scratchcard::try_from("Card three: 1 | 2").is_err());
     }
 
     #[test]
@@ -147,12 +182,9 @@ Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
 Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"#;
 
-        let original_scratchcards = puzzle_input
-            .lines()
-            .map(Scratchcard::try_from)
-            .collect::<Vec<Result<Scratchcard, ScratchcardError>>>();
+        let original_scratchcards = parse_input(puzzle_input).unwrap();
 
-        let answer = answer_part_1(&original_scratchcards);
+        let answer = answer_part_1(&original_scratchcards).unwrap();
 
         assert!(answer == 13)
     }
@@ -167,13 +199,38 @@ Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
 Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11"#;
 
         // Map of original scratchcards
-        let original_scratchcards = puzzle_input
-            .lines()
-            .map(Scratchcard::try_from)
-            .collect::<Vec<Result<Scratchcard, ScratchcardError>>>();
+        let original_scratchcards = parse_input(puzzle_input).unwrap();
 
-        let answer = answer_part_2(&original_scratchcards);
+        let answer = answer_part_2(&original_scratchcards).unwrap();
 
         assert!(answer == 30);
+        assert_eq!(
+            card_copies(&original_scratchcards).unwrap(),
+            vec![1, 2, 4, 8, 14, 1]
+        );
+    }
+
+    #[test]
+    fn test_card_copies_past_the_last_card() {
+        let puzzle_input = "Card 1: 1 2 3 | 1 2 3\nCard 2: 4 | 4";
+        let scratchcards = parse_input(puzzle_input).unwrap();
+
+        assert_eq!(card_copies(&scratchcards).unwrap(), vec![1, 2]);
+        assert_eq!(card_copies(&[]).unwrap(), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn test_card_copies_overflow() {
+        // Every card wins a copy of the next ten, so the copies grow faster than doubling
+        let puzzle_input = (1..=100)
+            .map(|id| format!("Card {id}: 1 2 3 4 5 6 7 8 9 10 | 1 2 3 4 5 6 7 8 9 10"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let scratchcards = parse_input(&puzzle_input).unwrap();
+
+        assert_eq!(
+            answer_part_2(&scratchcards).unwrap_err().to_string(),
+            "no solution: the number of scratchcards doesn't fit in 64 bits"
+        );
     }
 }
@@ -1,11 +1,16 @@
 use std::{
-    fs,
     io::{self, BufRead},
     ops::Range,
 };
 
+use crate::{
+    error::{column_of, Error, Location, Result},
+    solution::Solution,
+};
+
+/// One almanac section as `(destination start, source start, length)` triples sorted by source.
 #[derive(Debug, PartialEq)]
-struct AlmanacMap(Vec<(u64, u64, u64)>);
+pub struct AlmanacMap(pub Vec<(u64, u64, u64)>);
 
 impl AlmanacMap {
     pub fn process_map(&self, seed: u64) -> u64 {
@@ -20,21 +25,93 @@ impl AlmanacMap {
         }
         output
     }
+
+    /// Maps a whole range of seeds at once, splitting it wherever it crosses the edge of a source
+    /// range. The output ranges are in the same order as the parts of the input they come from.
+    /// Like `TryFrom` leaves them, the triples have to be sorted by source.
+    pub fn process_range(&self, seeds: Range<u64>) -> Result<Vec<Range<u64>>> {
+        let mut output = vec![];
+        let mut cursor = seeds.start;
+
+        for &(dest, source, range) in self.0.iter() {
+            let (Some(source_end), Some(_)) = (source.checked_add(range), dest.checked_add(range))
+            else {
+                return Err(Error::malformed(
+                    "ranges that fit in 64 bits",
+                    &format!("{dest} {source} {range}"),
+                    Location::default(),
+                ));
+            };
+            if source_end <= cursor {
+                continue;
+            }
+            if source >= seeds.end {
+                break;
+            }
+            // Seeds before this source range map to themselves
+            if source > cursor {
+                output.push(cursor..source);
+                cursor = source;
+            }
+            let end = seeds.end.min(source_end);
+            output.push((cursor - source + dest)..(end - source + dest));
+            cursor = end;
+        }
+        if cursor < seeds.end {
+            output.push(cursor..seeds.end);
+        }
+        Ok(output)
+    }
+
+    pub fn process_ranges(&self, seeds: &[Range<u64>]) -> Result<Vec<Range<u64>>> {
+        let mut output = vec![];
+        for range in seeds.iter().filter(|range| !range.is_empty()) {
+            output.extend(self.process_range(range.clone())?);
+        }
+        Ok(output)
+    }
 }
 
 // TODO: Implement TryFrom<T: BufRead> for AlmanacMap
 impl TryFrom<&Vec<String>> for AlmanacMap {
-    type Error = &'static str;
+    type Error = Error;
 
-    fn try_from(value: &Vec<String>) -> Result<Self, Self::Error> {
+    // Error lines are relative to the section, starting at its title line
+    fn try_from(value: &Vec<String>) -> Result<Self> {
         let mut triples = vec![];
-        for line in value {
-            if line.chars().next().unwrap().is_ascii_digit() {
-                let nums: Vec<Result<u64, Self::Error>> = line
+        for (idx, line) in value.iter().enumerate() {
+            if line.starts_with(|c: char| c.is_ascii_digit()) {
+                let nums = line
                     .split_whitespace()
-                    .map(|n| n.parse::<u64>().map_err(|_| "Can't parse map number"))
-                    .collect();
-                triples.push((nums[0]?, nums[1]?, nums[2]?));
+                    .map(|n| {
+                        n.parse::<u64>().map_err(|_| {
+                            Error::bad_number(
+                                n,
+                                Location {
+                                    line: Some(idx + 1),
+                                    column: column_of(line, n),
+                                },
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<u64>>>()?;
+                let [dest, source, range] = nums[..] else {
+                    return Err(Error::malformed(
+                        "`<destination> <source> <length>`",
+                        line,
+                        Location::default(),
+                    )
+                    .at_line(idx + 1));
+                };
+                if dest.checked_add(range).is_none() || source.checked_add(range).is_none() {
+                    return Err(Error::malformed(
+                        "ranges that fit in 64 bits",
+                        line,
+                        Location::default(),
+                    )
+                    .at_line(idx + 1));
+                }
+                triples.push((dest, source, range));
             }
         }
         triples.sort_by_key(|t| t.1);
@@ -55,96 +132,139 @@ pub fn split_str_by_empty_lines(input: &str) -> Vec<Vec<String>> {
             current_vec.clear();
         }
     }
-    if !final_vec.is_empty() {
-        final_vec.push(current_vec.clone());
-    }
-    return final_vec;
+    // The last section has no empty line after it, even when it is the only one
+    final_vec.push(current_vec);
+    final_vec
 }
 
-pub fn parse_seeds_from_str(input: &str) -> Option<Vec<u64>> {
-    if let Some((_, numbers_str)) = input.split_once("seeds: ") {
-        Some(
-            numbers_str
-                .split_whitespace()
-                .into_iter()
-                .map(|n| n.parse::<u64>().unwrap())
-                .collect(),
-        )
-    } else {
-        None
+pub fn parse_seeds_from_str(input: &str) -> Result<Vec<u64>> {
+    let Some((_, numbers_str)) = input.split_once("seeds: ") else {
+        return Err(Error::missing("`seeds:` line", Location::default()));
+    };
+    numbers_str
+        .split_whitespace()
+        .map(|n| {
+            n.parse::<u64>()
+                .map_err(|_| Error::bad_number(n, Location::default()))
+        })
+        .collect()
+}
+
+pub fn parse_seed_range_from_str(input: &str) -> Result<Vec<Range<u64>>> {
+    let numbers = parse_seeds_from_str(input)?;
+    if numbers.len() % 2 != 0 {
+        return Err(Error::malformed(
+            "seed ranges as `<start> <length>` pairs",
+            input,
+            Location::default(),
+        ));
     }
+    let mut pairs: Vec<(u64, u64)> = vec![];
+    for i in 0..(numbers.len() / 2) {
+        pairs.push((numbers[i * 2], numbers[(i * 2) + 1]));
+    }
+    pairs
+        .iter()
+        .map(|&(start, length)| {
+            let end = start.checked_add(length).ok_or_else(|| {
+                Error::malformed(
+                    "seed ranges that fit in 64 bits",
+                    &format!("{start} {length}"),
+                    Location::default(),
+                )
+            })?;
+            Ok(start..end)
+        })
+        .collect()
 }
 
-pub fn parse_seed_range_from_str(input: &str) -> Option<Vec<Range<u64>>> {
-    if let Some(numbers) = parse_seeds_from_str(input) {
-        let mut pairs: Vec<(u64, u64)> = vec![];
-        for i in 0..(numbers.len() / 2) {
-            pairs.push((numbers[i * 2], numbers[(i * 2) + 1]));
-        }
-        Some(
-            pairs
-                .iter()
-                .map(|(start, length)| start.clone()..(start + length).clone())
-                .collect::<Vec<Range<u64>>>(),
-        )
-    } else {
-        None
+fn seeds_line(sections: &[Vec<String>]) -> Result<&str> {
+    sections
+        .first()
+        .and_then(|section| section.first())
+        .map(|line| line.as_str())
+        .ok_or(Error::missing("`seeds:` line", Location::default()).at_line(1))
+}
+
+fn parse_maps(sections: &[Vec<String>]) -> Result<Vec<AlmanacMap>> {
+    if sections[1..].iter().all(|section| section.is_empty()) {
+        return Err(Error::missing("almanac maps", Location::default()));
     }
+    // Every section is followed by exactly one empty line
+    let mut section_start = sections.first().map_or(0, |section| section.len() + 1);
+    sections[1..]
+        .iter()
+        .map(|section| {
+            let map = AlmanacMap::try_from(section).map_err(|e| e.offset_lines(section_start));
+            section_start += section.len() + 1;
+            map
+        })
+        .collect()
 }
 
-pub fn answer_part_1(input: &str) -> u64 {
+/// The seeds, the same numbers read as seed ranges and the maps, in the order they are applied.
+pub type Almanac = (Vec<u64>, Vec<Range<u64>>, Vec<AlmanacMap>);
+
+pub fn parse_input(input: &str) -> Result<Almanac> {
     let input_lines = split_str_by_empty_lines(input);
-    let seeds = parse_seeds_from_str(&input_lines[0][0]);
-    let almanac_maps = input_lines[1..]
-        .iter()
-        .map(|line| AlmanacMap::try_from(line).unwrap())
-        .collect::<Vec<AlmanacMap>>();
+    let seeds_line = seeds_line(&input_lines)?;
+    let seeds = parse_seeds_from_str(seeds_line).map_err(|e| e.at_line(1))?;
+    let seed_ranges = parse_seed_range_from_str(seeds_line).map_err(|e| e.at_line(1))?;
+    Ok((seeds, seed_ranges, parse_maps(&input_lines)?))
+}
 
+pub fn answer_part_1(seeds: &[u64], almanac_maps: &[AlmanacMap]) -> Result<u64> {
     seeds
-        .unwrap()
         .iter()
         .map(|seed| {
             almanac_maps
                 .iter()
-                .fold(seed.clone(), |acc, map| map.process_map(acc))
+                .fold(*seed, |acc, map| map.process_map(acc))
         })
         .min()
-        .unwrap()
+        .ok_or(Error::NoSolution("there are no seeds"))
 }
 
-pub fn answer_part_2(input: &str) -> u64 {
-    let input_lines = split_str_by_empty_lines(input);
-    let seed_ranges = parse_seed_range_from_str(&input_lines[0][0]);
-    let almanac_maps = input_lines[1..]
+pub fn answer_part_2(seed_ranges: &[Range<u64>], almanac_maps: &[AlmanacMap]) -> Result<u64> {
+    almanac_maps
         .iter()
-        .map(|line| AlmanacMap::try_from(line).unwrap())
-        .collect::<Vec<AlmanacMap>>();
-
-    seed_ranges
-        .unwrap()
+        .try_fold(seed_ranges.to_vec(), |ranges, map| {
+            map.process_ranges(&ranges)
+        })?
         .iter()
-        .flat_map(|range| {
-            range.clone().map(|seed| {
-                almanac_maps
-                    .iter()
-                    .fold(seed.clone(), |acc, map| map.process_map(acc))
-            })
-        })
+        .filter(|range| !range.is_empty())
+        .map(|range| range.start)
         .min()
-        .unwrap()
+        .ok_or(Error::NoSolution("there are no seeds"))
 }
 
-pub fn answer() -> (u64, u64) {
-    let input = fs::read_to_string("puzzle5.txt").expect("Puzzle file not found.");
+pub struct Puzzle5;
+
+impl Solution for Puzzle5 {
+    type Input = Almanac;
+    type Answer = u64;
+
+    fn parse(&self, input: &str) -> Result<Self::Input> {
+        parse_input(input)
+    }
 
-    (answer_part_1(input.as_str()), answer_part_2(input.as_str()))
+    fn part_one(&self, (seeds, _, almanac_maps): &Self::Input) -> Result<Option<Self::Answer>> {
+        answer_part_1(seeds, almanac_maps).map(Some)
+    }
+
+    fn part_two(
+        &self,
+        (_, seed_ranges, almanac_maps): &Self::Input,
+    ) -> Result<Option<Self::Answer>> {
+        answer_part_2(seed_ranges, almanac_maps).map(Some)
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use crate::puzzle5::{
-        answer_part_1, answer_part_2, parse_seed_range_from_str, parse_seeds_from_str,
+        answer_part_1, answer_part_2, parse_input, parse_seed_range_from_str, parse_seeds_from_str,
         split_str_by_empty_lines, AlmanacMap,
     };
 
@@ -221,15 +341,61 @@ humidity-to-location map:
         // 6 - Make a function that does the above with a single seed and a vec of truples coming
         //   from an AlmanacMap enum.
 
-        let output_part_1 = answer_part_1(&puzzle_input);
+        let (seeds, seed_ranges, almanac_maps) = parse_input(puzzle_input).unwrap();
+
+        let output_part_1 = answer_part_1(&seeds, &almanac_maps).unwrap();
 
         assert_eq!(output_part_1, 35);
 
-        let output_part_2 = answer_part_2(&puzzle_input);
+        let output_part_2 = answer_part_2(&seed_ranges, &almanac_maps).unwrap();
 
         assert_eq!(output_part_2, 46)
     }
 
+    #[test]
+    fn test_almanac_errors() {
+        let error = parse_input("seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n52 x 48").unwrap_err();
+        assert_eq!(error.to_string(), "5:4: bad number 'x'");
+
+        let error = parse_input("seeds: 79 14\n\nseed-to-soil map:\n50 98").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "4: expected `<destination> <source> <length>`, found '50 98'"
+        );
+
+        let error =
+            parse_input("seeds: 79 14\n\nseed-to-soil map:\n0 18446744073709551615 2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "4: expected ranges that fit in 64 bits, found '0 18446744073709551615 2'"
+        );
+
+        let error =
+            parse_input("seeds: 18446744073709551615 2\n\nseed-to-soil map:\n50 98 2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "1: expected seed ranges that fit in 64 bits, found '18446744073709551615 2'"
+        );
+
+        // Both parts share the parsed seeds, so an odd count fails part one as well
+        let error = parse_input("seeds: 79 14 55\n\nseed-to-soil map:\n50 98 2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "1: expected seed ranges as `<start> <length>` pairs, found 'seeds: 79 14 55'"
+        );
+
+        assert_eq!(
+            parse_input("").unwrap_err().to_string(),
+            "1: missing `seeds:` line"
+        );
+        assert_eq!(
+            parse_input("seeds: 79 14\n").unwrap_err().to_string(),
+            "missing almanac maps"
+        );
+        assert!(parse_seed_range_from_str("seeds: 79 14 55").is_err());
+        assert!(parse_seeds_from_str("79 14").is_err());
+    }
+
     #[test]
     fn test_seed_to_map_process() {
         let seed = 79;
@@ -242,6 +408,40 @@ humidity-to-location map:
         assert_eq!(output, 81);
     }
 
+    #[test]
+    fn test_seed_range_to_map_process() {
+        let seed_to_soil_map = AlmanacMap(vec![(52, 50, 48), (50, 98, 2)]);
+
+        assert_eq!(
+            seed_to_soil_map.process_range(79..93).unwrap(),
+            vec![81..95]
+        );
+        assert_eq!(
+            seed_to_soil_map.process_range(40..110).unwrap(),
+            vec![40..50, 52..100, 50..52, 100..110]
+        );
+        assert_eq!(seed_to_soil_map.process_range(0..10).unwrap(), vec![0..10]);
+        assert!(AlmanacMap(vec![(u64::MAX, 0, 2)])
+            .process_range(0..10)
+            .is_err());
+
+        // Every seed of the split ranges matches the seed by seed mapping
+        let soil_to_fertilizer_map = AlmanacMap(vec![(39, 0, 15), (0, 15, 37), (37, 52, 2)]);
+        let mut mapped = soil_to_fertilizer_map
+            .process_range(10..60)
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect::<Vec<u64>>();
+        let mut expected = (10..60)
+            .map(|seed| soil_to_fertilizer_map.process_map(seed))
+            .collect::<Vec<u64>>();
+        mapped.sort();
+        expected.sort();
+
+        assert_eq!(mapped, expected);
+    }
+
     #[test]
     fn test_parse_map_input() {
         let input = "seed-to-soil map:\n\
@@ -262,6 +462,8 @@ humidity-to-location map:
     #[test]
     fn test_parse_set_of_maps() {
         let input = "
+            seeds: 79 14\n\
+            \n\
             seed-to-soil map:\n\
             50 98 2\n\
             52 50 48\n\
@@ -271,11 +473,7 @@ humidity-to-location map:
             37 52 2\n\
             39 0 15";
 
-        let almanac_maps = split_str_by_empty_lines(input.trim())
-            .iter()
-            .map(|line| AlmanacMap::try_from(line))
-            .collect::<Result<Vec<AlmanacMap>, _>>()
-            .unwrap();
+        let (_, _, almanac_maps) = parse_input(input.trim()).unwrap();
 
         assert_eq!(
             almanac_maps,
@@ -294,13 +492,16 @@ humidity-to-location map:
 
         let output = split_str_by_empty_lines(input);
 
-        assert_eq!(output, vec![vec!["a", "b", "c"], vec!["x", "y", "z"]])
+        assert_eq!(output, vec![vec!["a", "b", "c"], vec!["x", "y", "z"]]);
+        assert_eq!(split_str_by_empty_lines("a\nb"), vec![vec!["a", "b"]]);
     }
 
     #[test]
     fn test_multiple_map_processing() {
         let seed = 79;
         let input = "
+            seeds: 79 14\n\
+            \n\
             seed-to-soil map:\n\
             50 98 2\n\
             52 50 48\n\
@@ -310,10 +511,9 @@ humidity-to-location map:
             37 52 2\n\
             39 0 15";
 
-        let almanac_map_seed_to_soil_processing = split_str_by_empty_lines(input.trim())
+        let (_, _, almanac_maps) = parse_input(input.trim()).unwrap();
+        let almanac_map_seed_to_soil_processing = almanac_maps
             .iter()
-            .map(|line| AlmanacMap::try_from(line))
-            .flatten()
             .fold(seed, |acc, map| map.process_map(acc));
 
         assert_eq!(almanac_map_seed_to_soil_processing, 81)
@@ -1,56 +1,97 @@
-use std::fs;
-
-pub fn parse_input(input: &str) -> Vec<Record> {
-    let mut lines = input.lines().into_iter();
-    let time_records = lines
-        .next()
-        .map(|l| l.split_once("Time:"))
-        .flatten()
-        .map(|t| t.1.split_whitespace().collect::<Vec<&str>>())
-        .expect("Error while parsing time records");
-    let distance_records = lines
-        .next()
-        .map(|l| l.split_once("Distance:"))
-        .flatten()
-        .map(|t| t.1.split_whitespace().collect::<Vec<&str>>())
-        .expect("Error while parsing distance records");
-    time_records
-        .iter()
-        .zip(distance_records.iter())
-        .map(|records| Record {
-            time: records
-                .0
-                .parse::<u64>()
-                .expect("Error while parsing time records"),
-            distance: records
-                .1
-                .parse::<u64>()
-                .expect("Error while parsing distance records"),
+use std::ops::RangeInclusive;
+
+use crate::{
+    error::{column_of, Error, Location, Result},
+    solution::Solution,
+};
+
+// Returns the values after `title` on the given line, failing when the line or title is missing
+fn record_values<'a>(
+    line: Option<&'a str>,
+    title: &str,
+    expected: &'static str,
+    line_number: usize,
+) -> Result<&'a str> {
+    line.and_then(|l| l.split_once(title))
+        .map(|t| t.1)
+        .ok_or(Error::missing(expected, Location::default()).at_line(line_number))
+}
+
+fn parse_numbers(line: &str, values: &str, line_number: usize) -> Result<Vec<u64>> {
+    values
+        .split_whitespace()
+        .map(|n| {
+            n.parse::<u64>().map_err(|_| {
+                Error::bad_number(
+                    n,
+                    Location {
+                        line: Some(line_number),
+                        column: column_of(line, n),
+                    },
+                )
+            })
         })
         .collect()
 }
 
-pub fn parse_input_with_transform(input: &str) -> Record {
-    let mut lines = input.lines().into_iter();
-    let time_records = lines
-        .next()
-        .map(|l| l.split_once(":"))
-        .flatten()
-        .map(|t| t.1.replace(" ", ""))
-        .expect("Error while parsing time records");
-    let distance_records = lines
-        .next()
-        .map(|l| l.split_once(":"))
-        .flatten()
-        .map(|t| t.1.replace(" ", ""))
-        .expect("Error while parsing distance records");
-    Record {
+pub fn parse_input(input: &str) -> Result<Vec<Record>> {
+    let mut lines = input.lines();
+    let time_line = lines.next();
+    let time_records = parse_numbers(
+        time_line.unwrap_or_default(),
+        record_values(time_line, "Time:", "`Time:` line", 1)?,
+        1,
+    )?;
+    let distance_line = lines.next();
+    let distance_records = parse_numbers(
+        distance_line.unwrap_or_default(),
+        record_values(distance_line, "Distance:", "`Distance:` line", 2)?,
+        2,
+    )?;
+    if time_records.len() != distance_records.len() {
+        return Err(Error::malformed(
+            "one distance per race time",
+            distance_line.unwrap_or_default(),
+            Location::default(),
+        )
+        .at_line(2));
+    }
+    Ok(time_records
+        .iter()
+        .zip(distance_records.iter())
+        .map(|(&time, &distance)| Record { time, distance })
+        .collect())
+}
+
+pub fn parse_input_with_transform(input: &str) -> Result<Record> {
+    let mut lines = input.lines();
+    let time_records = record_values(lines.next(), ":", "`Time:` line", 1)?.replace(' ', "");
+    let distance_records =
+        record_values(lines.next(), ":", "`Distance:` line", 2)?.replace(' ', "");
+    Ok(Record {
         time: time_records
             .parse::<u64>()
-            .expect("Error while parsing time records"),
+            .map_err(|_| Error::bad_number(&time_records, Location::default()).at_line(1))?,
         distance: distance_records
             .parse::<u64>()
-            .expect("Error while parsing distance records"),
+            .map_err(|_| Error::bad_number(&distance_records, Location::default()).at_line(2))?,
+    })
+}
+
+/// Largest `r` with `r * r <= n`. Newton's method started above the root only moves down, so
+/// it stops on the floor of the root.
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let bits = 128 - n.leading_zeros();
+    let mut root = 1 << bits.div_ceil(2);
+    loop {
+        let next = (root + n / root) / 2;
+        if next >= root {
+            return root;
+        }
+        root = next;
     }
 }
 
@@ -67,57 +108,99 @@ impl Record {
         }
         (self.distance / self.time) as f64
     }
+
+    /// Whether holding the button for `hold` milliseconds travels further than the record.
+    pub fn is_beaten_by(&self, hold: u64) -> bool {
+        let Some(travel_time) = self.time.checked_sub(hold) else {
+            return false;
+        };
+        // Both factors fit in u64, so their product can't overflow u128
+        hold as u128 * travel_time as u128 > self.distance as u128
+    }
+
+    /// Range of hold times that beat the record, or `None` when it can't be beaten.
+    ///
+    /// The winning holds `s` are the integers strictly between the roots of
+    /// `s^2 - time * s + distance = 0`. The roots are estimated with an integer square root and
+    /// then nudged to the exact bounds, so there are no floating point rounding errors.
+    pub fn winning_holds(&self) -> Option<RangeInclusive<u64>> {
+        let time = self.time as u128;
+        let discriminant = (time * time).checked_sub(4 * self.distance as u128)?;
+        let root = isqrt(discriminant);
+
+        // Both estimates are within one of the exact bounds and lie in 0..=time
+        let mut low = ((time - root.min(time)) / 2) as u64;
+        let mut high = ((time + root) / 2).min(time) as u64;
+
+        while low <= high && !self.is_beaten_by(low) {
+            low += 1;
+        }
+        while low > 0 && self.is_beaten_by(low - 1) {
+            low -= 1;
+        }
+        while high >= low && !self.is_beaten_by(high) {
+            high = high.checked_sub(1)?;
+        }
+        while high < self.time && self.is_beaten_by(high + 1) {
+            high += 1;
+        }
+
+        (low <= high && self.is_beaten_by(low)).then_some(low..=high)
+    }
+
+    /// Number of hold times that beat the record.
+    pub fn ways_to_win(&self) -> u64 {
+        self.winning_holds()
+            .map_or(0, |holds| holds.end() - holds.start() + 1)
+    }
 }
 
-pub fn answer_part_1(input: &str) -> u64 {
-    let records = parse_input(input);
-    let speed_values = records
+pub fn answer_part_1(records: &[Record]) -> Result<u64> {
+    records
         .iter()
-        .map(|record| {
-            let speed = if record.speed() == record.speed().ceil() as f64 {
-                record.speed() + 1.0
-            } else {
-                record.speed().ceil()
-            } as u64;
-            let initial_speed = (speed..)
-                .find(|s| s * (record.time - s) > record.distance)
-                .expect("Can't calculate optimal speed.");
-            let speeds = (initial_speed..)
-                .take_while(|s| s * (record.time - s) > record.distance)
-                .collect::<Vec<_>>();
-            speeds.len() as u64
+        .try_fold(1_u64, |product, record| match record.ways_to_win() {
+            0 => Err(Error::NoSolution("the record can't be beaten")),
+            ways => product
+                .checked_mul(ways)
+                .ok_or(Error::NoSolution("the answer doesn't fit in 64 bits")),
         })
-        .collect::<Vec<u64>>();
-    speed_values.iter().product()
 }
 
-pub fn answer_part_2(input: &str) -> u64 {
-    let record = parse_input_with_transform(input);
+pub fn answer_part_2(record: &Record) -> Result<u64> {
+    match record.ways_to_win() {
+        0 => Err(Error::NoSolution("the record can't be beaten")),
+        ways => Ok(ways),
+    }
+}
 
-    let speed = if record.speed() == record.speed().ceil() {
-        record.speed() + 1.0
-    } else {
-        record.speed().ceil()
-    } as u64;
+pub struct Puzzle6;
 
-    let initial_speed = (speed..)
-        .find(|s| s * (record.time - s) > record.distance)
-        .expect("Can't calculate optimal speed.");
-    let speeds = (initial_speed..)
-        .take_while(|s| s * (record.time - s) > record.distance)
-        .collect::<Vec<_>>();
+impl Solution for Puzzle6 {
+    /// The races of part one and the single race part two reads from the same lines.
+    type Input = (Vec<Record>, Record);
+    type Answer = u64;
 
-    speeds.len() as u64
-}
+    fn parse(&self, input: &str) -> Result<Self::Input> {
+        Ok((parse_input(input)?, parse_input_with_transform(input)?))
+    }
 
-pub fn answer() -> (u64, u64) {
-    let input = fs::read_to_string("puzzle6.txt").expect("Puzzle file not found.");
-    (answer_part_1(input.as_str()), answer_part_2(input.as_str()))
+    fn part_one(&self, (records, _): &Self::Input) -> Result<Option<Self::Answer>> {
+        answer_part_1(records).map(Some)
+    }
+
+    fn part_two(&self, (_, record): &Self::Input) -> Result<Option<Self::Answer>> {
+        answer_part_2(record).map(Some)
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::puzzle6::{answer_part_1, answer_part_2, parse_input, Record};
+    use crate::{
+        error::Error,
+        puzzle6::{
+            answer_part_1, answer_part_2, isqrt, parse_input, parse_input_with_transform, Record,
+        },
+    };
 
     #[test]
     fn test_puzzle_answer_part_1() {
@@ -146,17 +229,88 @@ mod tests {
         //   - let result = speed_values.length()
         // 4. Multiply them.
         //   - results.product()
-        let result: u64 = answer_part_1(puzzle_input);
+        let result: u64 = answer_part_1(&parse_input(puzzle_input).unwrap()).unwrap();
         assert_eq!(result, 288);
     }
 
     #[test]
     fn test_answer_part_2() {
         let puzzle_input = "Time:      7  15   30\nDistance:  9  40  200";
-        let result: u64 = answer_part_2(puzzle_input);
+        let record = parse_input_with_transform(puzzle_input).unwrap();
+        let result: u64 = answer_part_2(&record).unwrap();
         assert_eq!(result, 71503);
     }
 
+    #[test]
+    fn test_isqrt() {
+        for n in 0..10_000_u128 {
+            let root = isqrt(n);
+            assert!(
+                root * root <= n && (root + 1) * (root + 1) > n,
+                "isqrt({n}) = {root}"
+            );
+        }
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt((u64::MAX as u128).pow(2)), u64::MAX as u128);
+        assert_eq!(isqrt((u64::MAX as u128).pow(2) - 1), u64::MAX as u128 - 1);
+    }
+
+    #[test]
+    fn test_record_winning_holds() {
+        let records = parse_input("Time:      7  15   30\nDistance:  9  40  200").unwrap();
+
+        assert_eq!(records[0].winning_holds(), Some(2..=5));
+        assert_eq!(records[1].winning_holds(), Some(4..=11));
+        assert_eq!(records[2].winning_holds(), Some(11..=19));
+        assert_eq!(
+            records
+                .iter()
+                .map(Record::ways_to_win)
+                .collect::<Vec<u64>>(),
+            vec![4, 8, 9]
+        );
+
+        let unbeatable = Record {
+            time: 4,
+            distance: 4,
+        };
+        assert_eq!(unbeatable.winning_holds(), None);
+        assert_eq!(unbeatable.ways_to_win(), 0);
+    }
+
+    #[test]
+    fn test_record_winning_holds_matches_scan() {
+        for time in 0..60 {
+            for distance in 0..(time * time / 4 + 2) {
+                let record = Record { time, distance };
+                let winning = (0..=time)
+                    .filter(|&hold| record.is_beaten_by(hold))
+                    .collect::<Vec<u64>>();
+
+                assert_eq!(
+                    record.winning_holds(),
+                    winning.first().zip(winning.last()).map(|(&l, &h)| l..=h)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_huge_values() {
+        let record = Record {
+            time: u64::MAX,
+            distance: 0,
+        };
+        assert_eq!(record.winning_holds(), Some(1..=u64::MAX - 1));
+        assert_eq!(record.ways_to_win(), u64::MAX - 1);
+
+        let record = Record {
+            time: u64::MAX,
+            distance: u64::MAX,
+        };
+        assert_eq!(record.winning_holds(), Some(2..=u64::MAX - 2));
+    }
+
     #[test]
     fn test_parse_puzzle_input() {
         // Parse time line
@@ -167,7 +321,7 @@ mod tests {
         // Do the same as with the time line, but use the prefix "Distance:"
         //
         let input = "Time:      7  15   30\nDistance:  9  40  200";
-        let records = parse_input(input);
+        let records = parse_input(input).unwrap();
 
         assert_eq!(
             records,
@@ -188,26 +342,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_puzzle_input_errors() {
+        let error = parse_input("Time:      7  15   30\nDistance:  9  4x  200").unwrap_err();
+        assert_eq!(error.to_string(), "2:15: bad number '4x'");
+
+        let error = parse_input("Time:      7  15   30").unwrap_err();
+        assert_eq!(error.to_string(), "2: missing `Distance:` line");
+
+        assert!(parse_input("Time: 7 15\nDistance: 9").is_err());
+        assert!(matches!(
+            answer_part_1(&[Record {
+                time: 3,
+                distance: 10
+            }]),
+            Err(Error::NoSolution(_))
+        ));
+    }
+
     #[test]
     fn test_transform_parsed_puzzle_input() {
         let input = "Time:      7  15   30\nDistance:  9  40  200";
-        let mut lines = input.lines();
-
-        let time_records = lines
-            .next()
-            .map(|l| l.split_once(" "))
-            .flatten()
-            .map(|t| (t.0, t.1.replace(" ", "")))
-            .map(|(title, value)| format!("{title} {value}"))
-            .expect("Error while parsing time records");
-        let distance_records = lines
-            .next()
-            .map(|l| l.split_once(" "))
-            .flatten()
-            .map(|t| (t.0, t.1.replace(" ", "")))
-            .map(|(title, value)| format!("{title} {value}"))
-            .expect("Error while parsing distance records");
-        let parsed_input = format!("{}\n{}", time_records, distance_records);
-        assert_eq!(parsed_input, "Time: 71530\nDistance: 940200");
+
+        assert_eq!(
+            parse_input_with_transform(input).unwrap(),
+            Record {
+                time: 71530,
+                distance: 940200
+            }
+        );
     }
 }
@@ -0,0 +1,44 @@
+// xorshift64: deterministic noise for generated test and benchmark inputs, without pulling in a
+// dependency. Not suitable for anything that needs real randomness.
+
+/// A seeded pseudo-random generator that gives the same sequence on every run.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    /// The seed must not be 0, which xorshift never leaves.
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "xorshift needs a non-zero seed");
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::Rng;
+
+    #[test]
+    fn test_rng_is_deterministic() {
+        let (mut first, mut second) = (Rng::new(2023), Rng::new(2023));
+        let numbers = (0..100).map(|_| first.below(10)).collect::<Vec<usize>>();
+
+        assert_eq!(
+            numbers,
+            (0..100).map(|_| second.below(10)).collect::<Vec<_>>()
+        );
+        assert!(numbers.iter().all(|&n| n < 10));
+        assert!((0..10).all(|n| numbers.contains(&n)));
+    }
+}
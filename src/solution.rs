@@ -0,0 +1,205 @@
+use std::fmt;
+
+use crate::{error::Result, puzzle1, puzzle2, puzzle3, puzzle4, puzzle5, puzzle6};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "Part one"),
+            Part::Two => write!(f, "Part two"),
+        }
+    }
+}
+
+/// A solver for a single day. The input is parsed once and shared by both parts.
+pub trait Solution {
+    type Input;
+    type Answer: fmt::Display;
+
+    fn parse(&self, input: &str) -> Result<Self::Input>;
+
+    /// Returns `Ok(None)` while the part has not been solved yet.
+    fn part_one(&self, input: &Self::Input) -> Result<Option<Self::Answer>>;
+
+    fn part_two(&self, input: &Self::Input) -> Result<Option<Self::Answer>>;
+}
+
+// Type-erased view of a `Solution` so days with different input and answer types can be stored
+// together in the registry.
+trait Runner {
+    fn parse(&self, input: &str) -> Result<Box<dyn Solver + '_>>;
+}
+
+// A parsed input bundled with the solution that parsed it, so it can't be handed to another day.
+trait Solver {
+    fn solve(&self, part: Part) -> Result<Option<String>>;
+}
+
+struct Registered<S>(S);
+
+struct Parsed<'a, S: Solution> {
+    solution: &'a S,
+    input: S::Input,
+}
+
+impl<S> Runner for Registered<S>
+where
+    S: Solution,
+    S::Input: 'static,
+{
+    fn parse(&self, input: &str) -> Result<Box<dyn Solver + '_>> {
+        Ok(Box::new(Parsed {
+            solution: &self.0,
+            input: self.0.parse(input)?,
+        }))
+    }
+}
+
+impl<S: Solution> Solver for Parsed<'_, S> {
+    fn solve(&self, part: Part) -> Result<Option<String>> {
+        let answer = match part {
+            Part::One => self.solution.part_one(&self.input),
+            Part::Two => self.solution.part_two(&self.input),
+        }?;
+        Ok(answer.map(|answer| answer.to_string()))
+    }
+}
+
+/// The input of a day, parsed once and ready to be solved for any part.
+pub struct ParsedInput<'a>(Box<dyn Solver + 'a>);
+
+impl ParsedInput<'_> {
+    pub fn solve(&self, part: Part) -> Result<Option<String>> {
+        self.0.solve(part)
+    }
+}
+
+pub struct Day {
+    pub number: u8,
+    runner: Box<dyn Runner>,
+}
+
+impl Day {
+    pub fn parse(&self, input: &str) -> Result<ParsedInput<'_>> {
+        self.runner.parse(input).map(ParsedInput)
+    }
+}
+
+#[derive(Default)]
+pub struct Registry {
+    days: Vec<Day>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    pub fn register<S>(&mut self, number: u8, solution: S) -> &mut Self
+    where
+        S: Solution + 'static,
+        S::Input: 'static,
+    {
+        let day = Day {
+            number,
+            runner: Box::new(Registered(solution)),
+        };
+        match self.days.binary_search_by_key(&number, |day| day.number) {
+            Ok(idx) => self.days[idx] = day,
+            Err(idx) => self.days.insert(idx, day),
+        }
+        self
+    }
+
+    pub fn get(&self, number: u8) -> Option<&Day> {
+        self.days.iter().find(|day| day.number == number)
+    }
+
+    pub fn days(&self) -> impl Iterator<Item = &Day> {
+        self.days.iter()
+    }
+
+    pub fn numbers(&self) -> Vec<u8> {
+        self.days().map(|day| day.number).collect()
+    }
+}
+
+/// Every solved day. Adding a day only needs a new registration here.
+pub fn registry() -> Registry {
+    let mut registry = Registry::new();
+    registry
+        .register(1, puzzle1::Puzzle1::default())
+        .register(2, puzzle2::Puzzle2::default())
+        .register(3, puzzle3::Puzzle3::default())
+        .register(4, puzzle4::Puzzle4)
+        .register(5, puzzle5::Puzzle5)
+        .register(6, puzzle6::Puzzle6);
+    registry
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        error::{Error, Location, Result},
+        solution::{registry, Part, Registry, Solution},
+    };
+
+    struct Doubler;
+
+    impl Solution for Doubler {
+        type Input = Vec<u32>;
+        type Answer = u32;
+
+        fn parse(&self, input: &str) -> Result<Self::Input> {
+            input
+                .split_whitespace()
+                .map(|n| {
+                    n.parse()
+                        .map_err(|_| Error::bad_number(n, Location::default()))
+                })
+                .collect()
+        }
+
+        fn part_one(&self, input: &Self::Input) -> Result<Option<Self::Answer>> {
+            Ok(Some(input.iter().sum::<u32>() * 2))
+        }
+
+        fn part_two(&self, _input: &Self::Input) -> Result<Option<Self::Answer>> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn test_registry_runs_registered_solution() {
+        let mut registry = Registry::new();
+        registry.register(9, Doubler);
+
+        let day = registry.get(9).unwrap();
+        let input = day.parse("1 2 3").unwrap();
+
+        assert_eq!(input.solve(Part::One).unwrap(), Some(String::from("12")));
+        assert_eq!(input.solve(Part::Two).unwrap(), None);
+        assert!(matches!(day.parse("1 x"), Err(Error::BadNumber { .. })));
+    }
+
+    #[test]
+    fn test_registry_keeps_days_sorted() {
+        let mut days = Registry::new();
+        days.register(3, Doubler)
+            .register(1, Doubler)
+            .register(3, Doubler);
+
+        assert_eq!(days.numbers(), vec![1, 3]);
+        assert_eq!(registry().numbers(), vec![1, 2, 3, 4, 5, 6]);
+    }
+}
@@ -0,0 +1,157 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    config,
+    error::{Error, Location, Result},
+    solution::{Day, Part},
+};
+
+/// File with the known answers, looked up next to the puzzle inputs.
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+/// Expected answers keyed by day and part, read from sections like `[day5]` with `part1 = ...`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Answers(BTreeMap<(u8, Part), String>);
+
+fn parse_answer_key(key: &str) -> Option<(u8, Part)> {
+    let (day, part) = key.split_once('.')?;
+    let day = day.strip_prefix("day")?.parse::<u8>().ok()?;
+    let part = match part {
+        "part1" => Part::One,
+        "part2" => Part::Two,
+        _ => return None,
+    };
+    Some((day, part))
+}
+
+impl Answers {
+    pub fn parse(input: &str) -> Result<Answers> {
+        config::parse_key_values(input)?
+            .into_iter()
+            .map(|(key, value)| match parse_answer_key(&key) {
+                Some(day_part) => Ok((day_part, value)),
+                None => Err(Error::malformed(
+                    "`[dayN]` sections with `part1`/`part2` answers",
+                    &key,
+                    Location::default(),
+                )),
+            })
+            .collect::<Result<BTreeMap<(u8, Part), String>>>()
+            .map(Answers)
+    }
+
+    pub fn expected(&self, day: u8, part: Part) -> Option<&str> {
+        self.0.get(&(day, part)).map(String::as_str)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail {
+        expected: String,
+    },
+    /// Either no answer is recorded or the part has not been solved yet.
+    Missing,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Pass => f.pad("PASS"),
+            Status::Fail { .. } => f.pad("FAIL"),
+            Status::Missing => f.pad("MISSING"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Verdict {
+    pub day: u8,
+    pub part: Part,
+    pub answer: Option<String>,
+    pub status: Status,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let answer = self.answer.as_deref().unwrap_or("-");
+        write!(
+            f,
+            "{:<7} puzzle #{} ({}): {answer}",
+            self.status, self.day, self.part
+        )?;
+        if let Status::Fail { expected } = &self.status {
+            write!(f, " (expected {expected})")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn verify_day(day: &Day, input: &str, answers: &Answers) -> Result<Vec<Verdict>> {
+    let parsed_input = day.parse(input)?;
+    Part::ALL
+        .iter()
+        .map(|&part| {
+            let answer = parsed_input.solve(part)?;
+            let status = match (answer.as_deref(), answers.expected(day.number, part)) {
+                (Some(answer), Some(expected)) if answer == expected => Status::Pass,
+                (Some(_), Some(expected)) => Status::Fail {
+                    expected: expected.to_string(),
+                },
+                _ => Status::Missing,
+            };
+            Ok(Verdict {
+                day: day.number,
+                part,
+                answer,
+                status,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        solution::{registry, Part},
+        verify::{verify_day, Answers, Status},
+    };
+
+    #[test]
+    fn test_parse_answers() {
+        let answers = Answers::parse("[day6]\npart1 = 288\npart2 = \"71503\"").unwrap();
+
+        assert_eq!(answers.expected(6, Part::One), Some("288"));
+        assert_eq!(answers.expected(6, Part::Two), Some("71503"));
+        assert_eq!(answers.expected(5, Part::One), None);
+
+        assert!(Answers::parse("[day6]\npart3 = 1").is_err());
+        assert!(Answers::parse("[six]\npart1 = 1").is_err());
+    }
+
+    #[test]
+    fn test_verify_day() {
+        let registry = registry();
+        let day = registry.get(6).unwrap();
+        let input = "Time:      7  15   30\nDistance:  9  40  200";
+
+        let answers = Answers::parse("[day6]\npart1 = 288\npart2 = 1").unwrap();
+        let verdicts = verify_day(day, input, &answers).unwrap();
+
+        assert_eq!(verdicts[0].status, Status::Pass);
+        assert_eq!(
+            verdicts[1].status,
+            Status::Fail {
+                expected: String::from("1")
+            }
+        );
+        assert_eq!(
+            verdicts[1].to_string(),
+            "FAIL    puzzle #6 (Part two): 71503 (expected 1)"
+        );
+
+        let verdicts = verify_day(day, input, &Answers::default()).unwrap();
+        assert!(verdicts.iter().all(|v| v.status == Status::Missing));
+    }
+}